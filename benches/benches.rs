@@ -0,0 +1,214 @@
+use criterion::{
+    criterion_group,
+    criterion_main,
+    BatchSize,
+    BenchmarkId,
+    Criterion,
+};
+use followers::{
+    background::Background,
+    camera::Camera,
+    color::Color,
+    config::{decode_config_str, encode_config_str, Config},
+    image::Image,
+    world::{DisplayParams, SimParams, World},
+};
+use rand::prelude::*;
+use rand_chacha::ChaCha20Rng;
+
+const PARTICLE_COUNTS: &[usize] = &[1000, 100000, 1000000];
+
+const IMAGE_WIDTH: usize = 1920;
+const IMAGE_HEIGHT: usize = 1080;
+
+// enough frames for the particles to spread out from the starting ring
+const WARM_UP_FRAMES: usize = 60;
+
+fn sim_params(particle_count: usize) -> SimParams {
+    SimParams {
+        particle_count,
+        ..SimParams::default()
+    }
+}
+
+fn warm_world(particle_count: usize) -> World {
+    let mut world =
+        World::new(&sim_params(particle_count), &DisplayParams::default())
+            .unwrap();
+    for _ in 0..WARM_UP_FRAMES {
+        world.update();
+    }
+    world
+}
+
+fn world_new(c: &mut Criterion) {
+    let mut group = c.benchmark_group("World::new");
+    group.sample_size(10);
+    for &particle_count in PARTICLE_COUNTS {
+        let sim_params = sim_params(particle_count);
+        let display_params = DisplayParams::default();
+        group.bench_with_input(
+            BenchmarkId::from_parameter(particle_count),
+            &particle_count,
+            |b, _| b.iter(|| World::new(&sim_params, &display_params).unwrap()),
+        );
+    }
+    group.finish();
+}
+
+fn world_update(c: &mut Criterion) {
+    let mut group = c.benchmark_group("World::update");
+    group.sample_size(10);
+    for &particle_count in PARTICLE_COUNTS {
+        let mut world = warm_world(particle_count);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(particle_count),
+            &particle_count,
+            |b, _| b.iter(|| world.update()),
+        );
+    }
+    group.finish();
+}
+
+fn world_render(c: &mut Criterion) {
+    let mut group = c.benchmark_group("World::render");
+    group.sample_size(10);
+    for &particle_count in PARTICLE_COUNTS {
+        let world = warm_world(particle_count);
+        let mut image =
+            Image::new(IMAGE_WIDTH, IMAGE_HEIGHT, Background::default());
+        let camera = Camera::default();
+        group.bench_with_input(
+            BenchmarkId::from_parameter(particle_count),
+            &particle_count,
+            |b, _| b.iter(|| world.render(&mut image, &camera)),
+        );
+    }
+    group.finish();
+}
+
+fn image_draw_particle(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Image::draw_particle");
+    group.sample_size(10);
+    let color = Color::hsva(120.0, 70.0, 100.0, 6.0);
+    for &particle_count in PARTICLE_COUNTS {
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let particles = (0..particle_count)
+            .map(|_| {
+                (
+                    rng.gen_range(-1.0..=IMAGE_WIDTH as f32),
+                    rng.gen_range(-1.0..=IMAGE_HEIGHT as f32),
+                )
+            })
+            .collect::<Vec<_>>();
+        let mut image =
+            Image::new(IMAGE_WIDTH, IMAGE_HEIGHT, Background::default());
+        group.bench_with_input(
+            BenchmarkId::from_parameter(particle_count),
+            &particle_count,
+            |b, _| {
+                b.iter(|| {
+                    for &(x, y) in &particles {
+                        image.draw_particle(x, y, color);
+                    }
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+fn image_resize(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Image::resize");
+    group.sample_size(10);
+    let sizes = [
+        ("shrink", (IMAGE_WIDTH / 2, IMAGE_HEIGHT / 2)),
+        ("grow", (IMAGE_WIDTH * 2, IMAGE_HEIGHT * 2)),
+        ("mixed", (IMAGE_WIDTH / 2, IMAGE_HEIGHT * 2)),
+    ];
+    for (name, (width, height)) in sizes {
+        group.bench_function(name, |b| {
+            b.iter_batched_ref(
+                || Image::new(IMAGE_WIDTH, IMAGE_HEIGHT, Background::default()),
+                |image| image.resize(width, height),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn world_generate_svg(c: &mut Criterion) {
+    let mut group = c.benchmark_group("World::generate_svg");
+    group.sample_size(10);
+    for &particle_count in PARTICLE_COUNTS {
+        let world = warm_world(particle_count);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(particle_count),
+            &particle_count,
+            |b, _| b.iter(|| world.generate_svg(&Background::default())),
+        );
+    }
+    group.finish();
+}
+
+// the config only grows with the particle count when it includes imported
+// partners
+fn config(particle_count: usize) -> Config {
+    let partners = (0..particle_count)
+        .map(|idx| [(idx + 1) % particle_count, (idx + 2) % particle_count])
+        .collect();
+    Config {
+        sim_params: SimParams {
+            partners: Some(partners),
+            ..sim_params(particle_count)
+        },
+        ..Config::default()
+    }
+}
+
+fn config_encode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("encode_config_str");
+    group.sample_size(10);
+    for &particle_count in PARTICLE_COUNTS {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(particle_count),
+            &particle_count,
+            |b, &particle_count| {
+                b.iter_batched(
+                    || config(particle_count),
+                    encode_config_str,
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+    }
+    group.finish();
+}
+
+fn config_decode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("decode_config_str");
+    group.sample_size(10);
+    for &particle_count in PARTICLE_COUNTS {
+        let config_str = encode_config_str(config(particle_count));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(particle_count),
+            &particle_count,
+            |b, _| b.iter(|| decode_config_str(&config_str).unwrap()),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    world_new,
+    world_update,
+    world_render,
+    image_draw_particle,
+    image_resize,
+    world_generate_svg,
+    config_encode,
+    config_decode,
+);
+criterion_main!(benches);
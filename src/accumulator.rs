@@ -0,0 +1,221 @@
+use crate::{
+    color::{srgb_from_linear, BlendMode, Color, LinearColor},
+    dither::Dither,
+    image::resize_centered,
+};
+use serde::{Deserialize, Serialize};
+
+pub(crate) const ENCODE_TABLE_SIZE: usize = 4096;
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize,
+)]
+pub enum ToneMapping {
+    #[default]
+    Linear,
+    Log,
+    Reinhard,
+    Filmic,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ToneMap {
+    pub tone_mapping: ToneMapping,
+    // in stops
+    pub exposure: f32,
+    pub gamma: f32,
+}
+
+// keeps the canvas in floating point linear light, so faint particles still
+// add up instead of rounding away, and tone maps it down to bytes for display
+pub struct Accumulator {
+    width: usize,
+    height: usize,
+    tone_map: ToneMap,
+    encode_table: Vec<f32>,
+    pixels: Vec<LinearColor>,
+    // the brightest channel since the last fade or clear, kept as particles
+    // are added so log tone mapping doesn't scan every pixel every frame,
+    // blend modes that darken can leave it a little high until the next fade
+    brightest: f32,
+}
+
+impl ToneMapping {
+    pub const ALL: &[Self] =
+        &[Self::Linear, Self::Log, Self::Reinhard, Self::Filmic];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Linear => "linear",
+            Self::Log => "log",
+            Self::Reinhard => "reinhard",
+            Self::Filmic => "filmic",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|mode| mode.name() == name)
+    }
+
+    // maps exposed linear light to 0..1, white is the brightest exposed value
+    // in the image
+    pub fn map(self, x: f32, white: f32) -> f32 {
+        match self {
+            Self::Linear => x,
+            Self::Log => {
+                if white > 0.0 {
+                    x.ln_1p() / white.ln_1p()
+                } else {
+                    0.0
+                }
+            },
+            Self::Reinhard => x / (1.0 + x),
+            // Narkowicz's fit of the ACES curve
+            Self::Filmic => {
+                (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14)
+            },
+        }
+    }
+}
+
+impl Accumulator {
+    pub fn new(
+        width: usize,
+        height: usize,
+        pixels: &[Color],
+        tone_map: ToneMap,
+    ) -> Self {
+        let pixels = pixels
+            .iter()
+            .copied()
+            .map(LinearColor::from_color)
+            .collect::<Vec<_>>();
+        Self {
+            width,
+            height,
+            tone_map,
+            encode_table: encode_table(tone_map.gamma),
+            brightest: brightest(&pixels),
+            pixels,
+        }
+    }
+
+    pub fn set_tone_map(&mut self, tone_map: ToneMap) {
+        if tone_map.gamma != self.tone_map.gamma {
+            self.encode_table = encode_table(tone_map.gamma);
+        }
+        self.tone_map = tone_map;
+    }
+
+    pub fn blend_pixel(
+        &mut self,
+        x: usize,
+        y: usize,
+        color: LinearColor,
+        blend_mode: BlendMode,
+    ) {
+        let p = &mut self.pixels[x + y * self.width];
+        *p = p.blend_mode(color, blend_mode);
+        self.brightest = self.brightest.max(p.r.max(p.g).max(p.b));
+    }
+
+    pub fn fade(&mut self, amount: f32, background: &[Color]) {
+        self.brightest = 0.0;
+        for (p, &background) in self.pixels.iter_mut().zip(background) {
+            *p = p.approach(LinearColor::from_color(background), amount);
+            self.brightest = self.brightest.max(p.r.max(p.g).max(p.b));
+        }
+    }
+
+    // the background is for the new size
+    pub fn resize(
+        &mut self,
+        width: usize,
+        height: usize,
+        background: &[Color],
+    ) {
+        self.pixels = resize_centered(
+            &self.pixels,
+            (self.width, self.height),
+            (width, height),
+            |idx| LinearColor::from_color(background[idx]),
+        );
+        self.brightest = brightest(&self.pixels);
+        self.width = width;
+        self.height = height;
+    }
+
+    pub fn clear(&mut self, background: &[Color]) {
+        for (p, &background) in self.pixels.iter_mut().zip(background) {
+            *p = LinearColor::from_color(background);
+        }
+        self.brightest = brightest(&self.pixels);
+    }
+
+    pub fn tone_map(&self, out: &mut [Color], dither: Dither) {
+        let ToneMap {
+            tone_mapping,
+            exposure,
+            gamma: _,
+        } = self.tone_map;
+        let scale = exposure.exp2();
+        let white = if tone_mapping == ToneMapping::Log {
+            self.brightest * scale
+        } else {
+            1.0
+        };
+        for (idx, (p, out)) in self.pixels.iter().zip(out).enumerate() {
+            let x = idx % self.width;
+            let y = idx / self.width;
+            let channel = |c: f32| {
+                let c = tone_mapping.map(c * scale, white);
+                encode(&self.encode_table, c, dither, x, y)
+            };
+            *out = Color {
+                r: channel(p.r),
+                g: channel(p.g),
+                b: channel(p.b),
+                a: dither.quantize(p.a.clamp(0.0, 1.0) * 255.0, x, y),
+            };
+        }
+    }
+}
+
+fn brightest(pixels: &[LinearColor]) -> f32 {
+    pixels
+        .iter()
+        .map(|p| p.r.max(p.g).max(p.b))
+        .fold(0.0, f32::max)
+}
+
+// gamma and sRGB encoding to 0..255, tabulated since it runs for every pixel
+// every frame
+pub(crate) fn encode_table(gamma: f32) -> Vec<f32> {
+    (0..ENCODE_TABLE_SIZE)
+        .map(|idx| {
+            let x = idx as f32 / (ENCODE_TABLE_SIZE - 1) as f32;
+            let x = x.powf(1.0 / gamma);
+            srgb_from_linear(x) * 255.0
+        })
+        .collect()
+}
+
+// encodes 0..1 through the table to a byte for the pixel, interpolating
+// between entries when dithering since the dark entries are most of a byte
+// apart
+pub(crate) fn encode(
+    table: &[f32],
+    c: f32,
+    dither: Dither,
+    x: usize,
+    y: usize,
+) -> u8 {
+    let pos = c.clamp(0.0, 1.0) * (ENCODE_TABLE_SIZE - 1) as f32;
+    let value = if dither == Dither::None {
+        table[pos.round() as usize]
+    } else {
+        let idx = (pos as usize).min(ENCODE_TABLE_SIZE - 2);
+        table[idx] + (table[idx + 1] - table[idx]) * (pos - idx as f32)
+    };
+    dither.quantize(value, x, y)
+}
@@ -0,0 +1,155 @@
+use crate::{color::Color, dither::Dither};
+use serde::{Deserialize, Serialize};
+
+pub const BACKGROUND_COLOR: Color = Color::hex(0x000000ff);
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize,
+)]
+pub enum BackgroundStyle {
+    #[default]
+    Solid,
+    Linear,
+    Radial,
+    Transparent,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Background {
+    pub style: BackgroundStyle,
+    // the solid color, or where the gradients start
+    pub color: Color,
+    // where the gradients end
+    pub end_color: Color,
+    // direction of the linear gradient, in degrees clockwise from pointing
+    // right
+    pub angle: f32,
+}
+
+impl BackgroundStyle {
+    pub const ALL: &[Self] =
+        &[Self::Solid, Self::Linear, Self::Radial, Self::Transparent];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Solid => "solid",
+            Self::Linear => "linear",
+            Self::Radial => "radial",
+            Self::Transparent => "transparent",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|style| style.name() == name)
+    }
+}
+
+impl Default for Background {
+    fn default() -> Self {
+        Self {
+            style: BackgroundStyle::Solid,
+            color: BACKGROUND_COLOR,
+            end_color: Color::hex(0x202040ff),
+            angle: 90.0,
+        }
+    }
+}
+
+impl Background {
+    pub fn solid(color: Color) -> Self {
+        Self {
+            style: BackgroundStyle::Solid,
+            color,
+            ..Self::default()
+        }
+    }
+
+    // the color everywhere, if it is the same everywhere
+    pub fn uniform(&self) -> Option<Color> {
+        match self.style {
+            BackgroundStyle::Solid => Some(self.color),
+            BackgroundStyle::Linear | BackgroundStyle::Radial => None,
+            BackgroundStyle::Transparent => Some(Color::transparent()),
+        }
+    }
+
+    // the background of every pixel of an image, the gradients are laid out
+    // the same way as the CSS ones from `css` so they match the SVG export
+    pub fn pixels(
+        &self,
+        width: usize,
+        height: usize,
+        dither: Dither,
+    ) -> Vec<Color> {
+        let Self {
+            style,
+            color,
+            end_color,
+            angle,
+        } = *self;
+        if let Some(color) = self.uniform() {
+            return vec![color; width * height];
+        }
+        let half_width = width as f32 / 2.0;
+        let half_height = height as f32 / 2.0;
+        let (sin, cos) = angle.to_radians().sin_cos();
+        // the gradient line is just long enough for the corners to get the
+        // start and end colors
+        let half_length = half_width * cos.abs() + half_height * sin.abs();
+        let half_diagonal = half_width.hypot(half_height);
+        let mut pixels = Vec::with_capacity(width * height);
+        for y in 0..height {
+            let dy = y as f32 + 0.5 - half_height;
+            for x in 0..width {
+                let dx = x as f32 + 0.5 - half_width;
+                let t = match style {
+                    BackgroundStyle::Linear => {
+                        if half_length > 0.0 {
+                            (dx * cos + dy * sin) / (2.0 * half_length) + 0.5
+                        } else {
+                            0.0
+                        }
+                    },
+                    _ => dx.hypot(dy) / half_diagonal,
+                };
+                let t = t.clamp(0.0, 1.0);
+                let channel = |from: u8, to: u8| {
+                    let value = from as f32 + (to as f32 - from as f32) * t;
+                    dither.quantize(value, x, y)
+                };
+                pixels.push(Color {
+                    r: channel(color.r, end_color.r),
+                    g: channel(color.g, end_color.g),
+                    b: channel(color.b, end_color.b),
+                    a: channel(color.a, end_color.a),
+                });
+            }
+        }
+        pixels
+    }
+
+    // a CSS background value that looks the same
+    pub fn css(&self) -> String {
+        let Self {
+            style,
+            color,
+            end_color,
+            angle,
+        } = *self;
+        let color = color.fmt_hex();
+        let end_color = end_color.fmt_hex();
+        match style {
+            BackgroundStyle::Solid => format!("#{color}"),
+            // CSS angles start pointing up
+            BackgroundStyle::Linear => format!(
+                "linear-gradient({}deg, #{color}, #{end_color})",
+                angle + 90.0
+            ),
+            BackgroundStyle::Radial => format!(
+                "radial-gradient(circle farthest-corner, #{color}, \
+                 #{end_color})"
+            ),
+            BackgroundStyle::Transparent => "transparent".to_owned(),
+        }
+    }
+}
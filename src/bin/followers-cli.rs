@@ -0,0 +1,251 @@
+use anyhow::{bail, Context, Result};
+use followers::{
+    config::{decode_config_str, encode_config_str, Config, CONFIG_VERSION},
+    explorer::{explore_seed, ExploreParams, SeedResult, SeedScore},
+    world::{Seed, SimParams},
+};
+use rand::prelude::*;
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    sync::atomic::{self, AtomicBool, AtomicUsize},
+    thread,
+};
+
+static USAGE: &str = "\
+usage: followers-cli <command> [options]
+
+commands:
+  explore    simulate random seeds and score how interesting they look
+
+explore options:
+  --config <config>        base configuration, from the `c` URL parameter
+  --particles <count>      particle count
+  --acc-limit <exp>        acceleration limit, as a power of 2
+  --speed-limit <exp>      speed limit, as a power of 2
+  --frames <count>         frames to simulate for each seed
+  --seeds <count>          seeds to try, 0 to run until stopped [default: 100]
+  --width <pixels>         width of the rendered image [default: 1280]
+  --height <pixels>        height of the rendered image [default: 720]
+  --thumbnails <dir>       directory to save PNG thumbnails of each seed to
+  --thumbnail-width <px>   width of the thumbnails [default: 320]
+  --jobs <count>           seeds to simulate in parallel [default: all cores]
+
+Results are written to stdout as tab-separated lines as each seed finishes,
+so they can be ranked with `sort -rn`.
+";
+
+struct ExploreArgs {
+    config: Config,
+    seed_count: usize,
+    width: usize,
+    height: usize,
+    thumbnails: Option<PathBuf>,
+    thumbnail_width: usize,
+    job_count: usize,
+}
+
+fn main() -> Result<()> {
+    let mut args = std::env::args().skip(1);
+    match args.next().as_deref() {
+        Some("explore") => explore(parse_explore_args(args)?),
+        Some("help" | "--help" | "-h") | None => {
+            print!("{USAGE}");
+            Ok(())
+        },
+        Some(command) => bail!("unknown command {command:?}\n\n{USAGE}"),
+    }
+}
+
+fn parse_explore_args(
+    mut args: impl Iterator<Item = String>,
+) -> Result<ExploreArgs> {
+    let mut config = Config::default();
+    let mut particle_count = None;
+    let mut acc_limit = None;
+    let mut speed_limit = None;
+    let mut frame_count = None;
+    let mut seed_count = 100;
+    let mut width = 1280;
+    let mut height = 720;
+    let mut thumbnails = None;
+    let mut thumbnail_width = 320;
+    let mut job_count = thread::available_parallelism().map_or(1, |n| n.get());
+
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .with_context(|| format!("missing value for {arg}"))
+        };
+        macro_rules! parse {
+            () => {
+                value()?
+                    .parse()
+                    .with_context(|| format!("invalid value for {arg}"))?
+            };
+        }
+        match arg.as_str() {
+            "--config" => {
+                config = decode_config_str(&value()?)
+                    .context("invalid value for --config")?;
+            },
+            "--particles" => particle_count = Some(parse!()),
+            "--acc-limit" => acc_limit = Some(parse!()),
+            "--speed-limit" => speed_limit = Some(parse!()),
+            "--frames" => frame_count = Some(parse!()),
+            "--seeds" => seed_count = parse!(),
+            "--width" => width = parse!(),
+            "--height" => height = parse!(),
+            "--thumbnails" => thumbnails = Some(PathBuf::from(value()?)),
+            "--thumbnail-width" => thumbnail_width = parse!(),
+            "--jobs" => job_count = parse!(),
+            _ => bail!("unknown option {arg:?}\n\n{USAGE}"),
+        }
+    }
+
+    // options override the base config
+    if let Some(particle_count) = particle_count {
+        config.sim_params.particle_count = particle_count;
+    }
+    if let Some(acc_limit) = acc_limit {
+        config.sim_params.acc_limit = acc_limit;
+    }
+    if let Some(speed_limit) = speed_limit {
+        config.sim_params.speed_limit = speed_limit;
+    }
+    if let Some(frame_count) = frame_count {
+        config.frame_limit = frame_count;
+    }
+
+    if width == 0 || height == 0 || thumbnail_width == 0 {
+        bail!("image sizes must be positive");
+    }
+
+    Ok(ExploreArgs {
+        config,
+        seed_count,
+        width,
+        height,
+        thumbnails,
+        thumbnail_width,
+        job_count: job_count.max(1),
+    })
+}
+
+fn explore(args: ExploreArgs) -> Result<()> {
+    let ExploreArgs {
+        config,
+        seed_count,
+        width,
+        height,
+        thumbnails,
+        thumbnail_width,
+        job_count,
+    } = args;
+
+    if let Some(thumbnails) = &thumbnails {
+        std::fs::create_dir_all(thumbnails).with_context(|| {
+            format!("failed to create {}", thumbnails.display())
+        })?;
+    }
+
+    let explore_params = ExploreParams {
+        frame_count: config.frame_limit,
+        width,
+        height,
+        camera: config.camera.clone(),
+    };
+    let thumbnail_height = (height * thumbnail_width / width).max(1);
+
+    println!("score\tcoverage\tentropy\tspread\tseed\tconfig");
+
+    let next_seed_idx = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+    thread::scope(|scope| {
+        let workers = (0..job_count)
+            .map(|_| {
+                scope.spawn(|| -> Result<()> {
+                    let mut rng = thread_rng();
+                    while !failed.load(atomic::Ordering::SeqCst) {
+                        let seed_idx = next_seed_idx
+                            .fetch_add(1, atomic::Ordering::SeqCst);
+                        if seed_count > 0 && seed_idx >= seed_count {
+                            break;
+                        }
+                        let seed = Seed::from_hash(rng.gen::<u64>());
+                        explore_one(
+                            &config,
+                            &explore_params,
+                            thumbnails.as_deref(),
+                            (thumbnail_width, thumbnail_height),
+                            seed,
+                        )
+                        .inspect_err(|_| {
+                            failed.store(true, atomic::Ordering::SeqCst);
+                        })?;
+                    }
+                    Ok(())
+                })
+            })
+            .collect::<Vec<_>>();
+        workers
+            .into_iter()
+            .try_for_each(|worker| worker.join().unwrap())
+    })
+}
+
+fn explore_one(
+    config: &Config,
+    explore_params: &ExploreParams,
+    thumbnails: Option<&Path>,
+    (thumbnail_width, thumbnail_height): (usize, usize),
+    seed: Seed,
+) -> Result<()> {
+    let SeedResult { seed, score, image } = explore_seed(
+        &config.sim_params,
+        &config.display_params,
+        explore_params,
+        seed,
+    )?;
+    let SeedScore {
+        score,
+        coverage,
+        entropy,
+        spread,
+    } = score;
+    let seed_hash = seed.fmt_hash();
+
+    if let Some(thumbnails) = thumbnails {
+        let path = thumbnails.join(format!("{seed_hash}.png"));
+        let png = image
+            .scaled(thumbnail_width, thumbnail_height)
+            .encode_png()?;
+        std::fs::write(&path, png)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+    }
+
+    let config_str = encode_config_str(Config {
+        version: CONFIG_VERSION,
+        sim_params: SimParams {
+            seed,
+            ..config.sim_params.clone()
+        },
+        display_params: config.display_params.clone(),
+        frame_limit: config.frame_limit,
+        camera: config.camera.clone(),
+    });
+
+    let columns = [
+        format!("{score:.6}"),
+        format!("{coverage:.6}"),
+        format!("{entropy:.6}"),
+        format!("{spread:.6}"),
+        seed_hash.to_string(),
+        config_str,
+    ];
+    let mut stdout = std::io::stdout().lock();
+    writeln!(stdout, "{}", columns.join("\t"))?;
+    stdout.flush()?;
+
+    Ok(())
+}
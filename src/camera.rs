@@ -0,0 +1,261 @@
+use crate::{math::Vec2, world::World};
+use serde::{Deserialize, Serialize};
+
+// how much room to leave around the particles when fitting them in view
+const FIT_MARGIN: f32 = 0.1;
+// fit again once the particles could be shown this much bigger
+const FIT_MAX_UNDERSCALE: f32 = 4.0;
+// follow again once the centroid is this far from the center, as a fraction
+// of the smaller side of the image
+const FOLLOW_MAX_DRIFT: f32 = 0.25;
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize,
+)]
+pub enum CameraMode {
+    #[default]
+    Manual,
+    Fit,
+    Follow,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Camera {
+    pub center_x: f32,
+    pub center_y: f32,
+    // pixels per world unit
+    pub zoom: f32,
+    // in degrees
+    pub rotation: f32,
+    pub mode: CameraMode,
+}
+
+// maps world positions to image coordinates for a camera and image size
+#[derive(Debug, Clone, Copy)]
+pub struct CameraTransform {
+    center: Vec2,
+    zoom: f32,
+    cos: f32,
+    sin: f32,
+    half_width: f32,
+    half_height: f32,
+}
+
+impl CameraMode {
+    pub const ALL: &[Self] = &[Self::Manual, Self::Fit, Self::Follow];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Manual => "manual",
+            Self::Fit => "fit",
+            Self::Follow => "follow",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|mode| mode.name() == name)
+    }
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self {
+            center_x: 0.0,
+            center_y: 0.0,
+            zoom: 1.0,
+            rotation: 0.0,
+            mode: CameraMode::Manual,
+        }
+    }
+}
+
+impl Camera {
+    pub fn transform(&self, width: usize, height: usize) -> CameraTransform {
+        let Self {
+            center_x,
+            center_y,
+            zoom,
+            rotation,
+            mode: _,
+        } = self;
+        let (sin, cos) = rotation.to_radians().sin_cos();
+        CameraTransform {
+            center: Vec2::new(*center_x, *center_y),
+            zoom: *zoom,
+            cos,
+            sin,
+            half_width: width as f32 / 2.0,
+            half_height: height as f32 / 2.0,
+        }
+    }
+
+    pub fn pan(&mut self, dx: f32, dy: f32) {
+        let Self {
+            center_x,
+            center_y,
+            zoom,
+            rotation,
+            mode: _,
+        } = self;
+        // undo the rotation so the world moves with the pointer
+        let (sin, cos) = rotation.to_radians().sin_cos();
+        *center_x -= (dx * cos + dy * sin) / *zoom;
+        *center_y -= (-dx * sin + dy * cos) / *zoom;
+    }
+
+    // keeps the world position under the image point in place
+    pub fn zoom_at(
+        &mut self,
+        zoom: f32,
+        (x, y): (f32, f32),
+        width: usize,
+        height: usize,
+    ) {
+        let anchor = self.transform(width, height).invert(x, y);
+        self.zoom = zoom;
+        let moved = self.transform(width, height).apply(anchor);
+        self.pan(x - moved.0, y - moved.1);
+    }
+
+    // the camera to render with given the view it was last rendered with, fit
+    // and follow keep moving that view on their own, so only the manual camera
+    // and the rotation are kept here
+    pub fn view(&self, tracked: &Camera) -> Camera {
+        match self.mode {
+            CameraMode::Manual => self.clone(),
+            CameraMode::Fit | CameraMode::Follow => Camera {
+                rotation: self.rotation,
+                mode: self.mode,
+                ..tracked.clone()
+            },
+        }
+    }
+
+    // moves the camera for the fit and follow modes, only when the particles
+    // have moved far enough that the view needs to change since the image has
+    // to be rendered again from the history when it does
+    pub fn track(
+        &mut self,
+        world: &World,
+        width: usize,
+        height: usize,
+    ) -> bool {
+        match self.mode {
+            CameraMode::Manual => false,
+            CameraMode::Fit => {
+                // everything still in the history gets rendered again, so
+                // both checks and the new view use all of it, checking just the
+                // current positions would refit every frame for a swarm that is
+                // much smaller than its trails
+                let (min, max) = if let Some(bounds) =
+                    bounds(world.history().iter().flatten())
+                {
+                    bounds
+                } else {
+                    return false;
+                };
+                let transform = self.transform(width, height);
+                let out_of_view = [
+                    (min.x, min.y),
+                    (min.x, max.y),
+                    (max.x, min.y),
+                    (max.x, max.y),
+                ]
+                .into_iter()
+                .any(|(x, y)| {
+                    let (x, y) = transform.apply(Vec2::new(x, y));
+                    x < 0.0 || y < 0.0 || x > width as f32 || y > height as f32
+                });
+                let zoom = fit_zoom(min, max, width, height);
+                if !out_of_view && zoom < self.zoom * FIT_MAX_UNDERSCALE {
+                    return false;
+                }
+                let center_x = (min.x + max.x) / 2.0;
+                let center_y = (min.y + max.y) / 2.0;
+                if (center_x, center_y, zoom)
+                    == (self.center_x, self.center_y, self.zoom)
+                {
+                    return false;
+                }
+                self.center_x = center_x;
+                self.center_y = center_y;
+                self.zoom = zoom;
+                true
+            },
+            CameraMode::Follow => {
+                let positions = world.positions();
+                if positions.is_empty() {
+                    return false;
+                }
+                let sum = positions
+                    .iter()
+                    .fold(Vec2::new(0.0, 0.0), |sum, &pos| sum + pos);
+                let centroid = sum * (1.0 / positions.len() as f32);
+                let (x, y) = self.transform(width, height).apply(centroid);
+                let dx = x - width as f32 / 2.0;
+                let dy = y - height as f32 / 2.0;
+                let max_drift = FOLLOW_MAX_DRIFT * width.min(height) as f32;
+                if dx * dx + dy * dy <= max_drift * max_drift {
+                    return false;
+                }
+                self.center_x = centroid.x;
+                self.center_y = centroid.y;
+                true
+            },
+        }
+    }
+}
+
+impl CameraTransform {
+    pub fn apply(&self, pos: Vec2) -> (f32, f32) {
+        let Self {
+            center,
+            zoom,
+            cos,
+            sin,
+            half_width,
+            half_height,
+        } = self;
+        let d = pos - *center;
+        let x = (d.x * cos - d.y * sin) * zoom + half_width;
+        let y = (d.x * sin + d.y * cos) * zoom + half_height;
+        (x, y)
+    }
+
+    pub fn invert(&self, x: f32, y: f32) -> Vec2 {
+        let Self {
+            center,
+            zoom,
+            cos,
+            sin,
+            half_width,
+            half_height,
+        } = self;
+        let x = (x - half_width) / zoom;
+        let y = (y - half_height) / zoom;
+        Vec2::new(x * cos + y * sin, -x * sin + y * cos) + *center
+    }
+}
+
+fn bounds<'a>(
+    positions: impl IntoIterator<Item = &'a Vec2>,
+) -> Option<(Vec2, Vec2)> {
+    positions.into_iter().fold(None, |bounds, &pos| {
+        Some(bounds.map_or((pos, pos), |(min, max): (Vec2, Vec2)| {
+            (
+                Vec2::new(min.x.min(pos.x), min.y.min(pos.y)),
+                Vec2::new(max.x.max(pos.x), max.y.max(pos.y)),
+            )
+        }))
+    })
+}
+
+// the zoom that fits the bounds at any rotation
+fn fit_zoom(min: Vec2, max: Vec2, width: usize, height: usize) -> f32 {
+    let d = max - min;
+    let diameter = d.length_squared().sqrt() * (1.0 + 2.0 * FIT_MARGIN);
+    if diameter == 0.0 {
+        return 1.0;
+    }
+    width.min(height) as f32 / diameter
+}
@@ -1,7 +1,18 @@
-use std::fmt;
+use serde::{Deserialize, Serialize};
+use std::{fmt, sync::OnceLock};
 use zerocopy::{Immutable, IntoBytes};
 
-#[derive(Debug, Clone, Copy, IntoBytes, Immutable)]
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    IntoBytes,
+    Immutable,
+    Serialize,
+    Deserialize,
+)]
 #[repr(C)]
 pub struct Color {
     pub r: u8,
@@ -10,8 +21,74 @@ pub struct Color {
     pub a: u8,
 }
 
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize,
+)]
+pub enum BlendMode {
+    #[default]
+    Normal,
+    Add,
+    Screen,
+    Multiply,
+    Lighten,
+    Darken,
+    Difference,
+}
+
+// which encoding colors are mixed in when blending, mixing the stored sRGB
+// values darkens where complementary colors overlap
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize,
+)]
+pub enum BlendSpace {
+    #[default]
+    Srgb,
+    Linear,
+}
+
+// how the hue, saturation and brightness ranges of particle colors are
+// interpreted, in OKLCH they are the hue, chroma and lightness
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize,
+)]
+pub enum ColorSpace {
+    #[default]
+    Hsv,
+    Oklch,
+}
+
+// straight (not premultiplied) color with linear light channels, which can
+// go above 1
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinearColor {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+// perceptual lightness and opponent color axes, see
+// https://bottosson.github.io/posts/oklab/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OkLab {
+    pub l: f32,
+    pub a: f32,
+    pub b: f32,
+}
+
+// OKLab in polar form, the hue is in degrees
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OkLch {
+    pub l: f32,
+    pub c: f32,
+    pub h: f32,
+}
+
 const BYTE_MAX_FLOAT: f32 = 0xff as f32;
 
+// a bit more than the most saturated sRGB colors have
+pub const OKLCH_MAX_CHROMA: f32 = 0.4;
+
 impl Color {
     pub const fn transparent() -> Self {
         Self {
@@ -30,6 +107,17 @@ impl Color {
         Self { r, g, b, a }
     }
 
+    // parses `rrggbb` or `rrggbbaa`, with or without a leading `#`
+    pub fn parse_hex(s: &str) -> Option<Self> {
+        let s = s.strip_prefix('#').unwrap_or(s);
+        let rgba = match s.len() {
+            6 => u32::from_str_radix(s, 16).ok()? << 8 | 0xff,
+            8 => u32::from_str_radix(s, 16).ok()?,
+            _ => return None,
+        };
+        Some(Self::hex(rgba))
+    }
+
     pub fn hsva(mut h: f32, mut s: f32, mut v: f32, mut a: f32) -> Self {
         h = h.rem_euclid(360.0);
         s = s.clamp(0.0, 100.0);
@@ -58,13 +146,6 @@ impl Color {
         let r = r + m;
         let g = g + m;
         let b = b + m;
-        fn srgb_from_linear(x: f32) -> f32 {
-            if x <= 0.0031308 {
-                x * 12.92
-            } else {
-                x.powf(1.0 / 2.4) * 1.055 - 0.055
-            }
-        }
         let r = srgb_from_linear(r);
         let g = srgb_from_linear(g);
         let b = srgb_from_linear(b);
@@ -75,7 +156,31 @@ impl Color {
         Self { r, g, b, a }
     }
 
+    // lightness and alpha in percent like `hsva`, chroma is usually below
+    // `OKLCH_MAX_CHROMA`, colors outside of sRGB are brought in by reducing
+    // the chroma
+    pub fn oklcha(l: f32, c: f32, h: f32, a: f32) -> Self {
+        let lch = OkLch {
+            l: l.clamp(0.0, 100.0) / 100.0,
+            c: c.max(0.0),
+            h,
+        };
+        let LinearColor { r, g, b, a: _ } =
+            lch.gamut_map().to_lab().to_linear();
+        LinearColor {
+            r,
+            g,
+            b,
+            a: a.clamp(0.0, 100.0) / 100.0,
+        }
+        .to_color()
+    }
+
     pub fn blend(self, other: Color) -> Self {
+        self.blend_mode(other, BlendMode::Normal)
+    }
+
+    pub fn blend_mode(self, other: Color, mode: BlendMode) -> Self {
         let Self {
             r: top_r,
             g: top_g,
@@ -98,6 +203,12 @@ impl Color {
         let bot_b = bot_b as f32 / BYTE_MAX_FLOAT;
         let bot_a = bot_a as f32 / BYTE_MAX_FLOAT;
 
+        // the top color is mixed with the blended color by how opaque the
+        // bottom is, then composited source-over
+        let top_r = mode.mix(bot_r, top_r, bot_a).min(1.0);
+        let top_g = mode.mix(bot_g, top_g, bot_a).min(1.0);
+        let top_b = mode.mix(bot_b, top_b, bot_a).min(1.0);
+
         let top_a_inv = 1.0 - top_a;
         let a = top_a + bot_a * top_a_inv;
         let r = (top_r * top_a + bot_r * bot_a * top_a_inv) / a;
@@ -112,17 +223,342 @@ impl Color {
         Self { r, g, b, a }
     }
 
+    // like `blend_mode` but decoded to linear light to composite, then
+    // encoded again
+    pub fn blend_mode_linear(self, other: Color, mode: BlendMode) -> Self {
+        LinearColor::from_color(self)
+            .blend_mode(LinearColor::from_color(other), mode)
+            .to_color()
+    }
+
+    pub fn luminance(self) -> f32 {
+        let Self { r, g, b, a: _ } = self;
+        (0.2126 * r as f32 + 0.7152 * g as f32 + 0.0722 * b as f32)
+            / BYTE_MAX_FLOAT
+    }
+
     pub fn fade(mut self, mut alpha: f32) -> Self {
         alpha = alpha.clamp(0.0, 1.0);
         self.a = (self.a as f32 * alpha) as u8;
         self
     }
 
+    // moves each channel the given fraction of the way to the target, the
+    // fraction of a step that doesn't fit in a u8 is carried in the remainder
+    // so slow fades still add up instead of rounding to nothing or to a whole
+    // step every time. the rounding is moved by the offset for dithering.
+    pub fn approach(
+        self,
+        target: Color,
+        amount: f32,
+        remainder: &mut [f32; 4],
+        offset: f32,
+    ) -> Self {
+        let amount = amount.clamp(0.0, 1.0);
+        let channel = |from: u8, to: u8, remainder: &mut f32| {
+            let from = from as f32 + *remainder;
+            let exact = from + (to as f32 - from) * amount;
+            let rounded = (exact + offset).round().clamp(0.0, 255.0);
+            *remainder = exact - rounded;
+            rounded as u8
+        };
+        let [r, g, b, a] = remainder;
+        Self {
+            r: channel(self.r, target.r, r),
+            g: channel(self.g, target.g, g),
+            b: channel(self.b, target.b, b),
+            a: channel(self.a, target.a, a),
+        }
+    }
+
+    // interpolates each channel as stored, like CSS gradients do by default
+    pub fn lerp(self, other: Color, t: f32) -> Self {
+        fn channel(from: u8, to: u8, t: f32) -> u8 {
+            (from as f32 + (to as f32 - from as f32) * t).round() as u8
+        }
+        let t = t.clamp(0.0, 1.0);
+        Self {
+            r: channel(self.r, other.r, t),
+            g: channel(self.g, other.g, t),
+            b: channel(self.b, other.b, t),
+            a: channel(self.a, other.a, t),
+        }
+    }
+
     pub fn fmt_hex(self) -> ColorHex {
         ColorHex(self)
     }
 }
 
+impl BlendSpace {
+    pub const ALL: &[Self] = &[Self::Srgb, Self::Linear];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Srgb => "srgb",
+            Self::Linear => "linear",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|space| space.name() == name)
+    }
+}
+
+impl ColorSpace {
+    pub const ALL: &[Self] = &[Self::Hsv, Self::Oklch];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Hsv => "hsv",
+            Self::Oklch => "oklch",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|space| space.name() == name)
+    }
+
+    // hue in degrees, the rest in percent, with the saturation as a
+    // percentage of `OKLCH_MAX_CHROMA` for OKLCH
+    pub fn color(self, h: f32, s: f32, v: f32, a: f32) -> Color {
+        match self {
+            Self::Hsv => Color::hsva(h, s, v, a),
+            Self::Oklch => Color::oklcha(
+                v,
+                s.clamp(0.0, 100.0) / 100.0 * OKLCH_MAX_CHROMA,
+                h,
+                a,
+            ),
+        }
+    }
+}
+
+impl BlendMode {
+    pub const ALL: &[Self] = &[
+        Self::Normal,
+        Self::Add,
+        Self::Screen,
+        Self::Multiply,
+        Self::Lighten,
+        Self::Darken,
+        Self::Difference,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Normal => "normal",
+            Self::Add => "add",
+            Self::Screen => "screen",
+            Self::Multiply => "multiply",
+            Self::Lighten => "lighten",
+            Self::Darken => "darken",
+            Self::Difference => "difference",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|mode| mode.name() == name)
+    }
+
+    // blends a single channel, leaving it as is over a transparent bottom
+    fn mix(self, bot: f32, top: f32, bot_a: f32) -> f32 {
+        let blended = match self {
+            Self::Normal => return top,
+            Self::Add => bot + top,
+            Self::Screen => bot + top - bot * top,
+            Self::Multiply => bot * top,
+            Self::Lighten => bot.max(top),
+            Self::Darken => bot.min(top),
+            Self::Difference => (bot - top).abs(),
+        };
+        top * (1.0 - bot_a) + blended * bot_a
+    }
+}
+
+impl LinearColor {
+    // clamped to the displayable range
+    pub fn to_color(self) -> Color {
+        let encode = |x: f32| {
+            (srgb_from_linear(x.clamp(0.0, 1.0)) * BYTE_MAX_FLOAT).round() as u8
+        };
+        Color {
+            r: encode(self.r),
+            g: encode(self.g),
+            b: encode(self.b),
+            a: (self.a.clamp(0.0, 1.0) * BYTE_MAX_FLOAT).round() as u8,
+        }
+    }
+
+    pub fn from_color(color: Color) -> Self {
+        let Color { r, g, b, a } = color;
+        Self {
+            r: linear_from_srgb_byte(r),
+            g: linear_from_srgb_byte(g),
+            b: linear_from_srgb_byte(b),
+            a: a as f32 / BYTE_MAX_FLOAT,
+        }
+    }
+
+    pub fn fade(mut self, alpha: f32) -> Self {
+        self.a *= alpha.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn blend_mode(self, other: LinearColor, mode: BlendMode) -> Self {
+        let Self {
+            r: top_r,
+            g: top_g,
+            b: top_b,
+            a: top_a,
+        } = other;
+        let Self {
+            r: bot_r,
+            g: bot_g,
+            b: bot_b,
+            a: bot_a,
+        } = self;
+
+        let top_r = mode.mix(bot_r, top_r, bot_a);
+        let top_g = mode.mix(bot_g, top_g, bot_a);
+        let top_b = mode.mix(bot_b, top_b, bot_a);
+
+        let top_a_inv = 1.0 - top_a;
+        let a = top_a + bot_a * top_a_inv;
+        if a == 0.0 {
+            return Self {
+                r: 0.0,
+                g: 0.0,
+                b: 0.0,
+                a: 0.0,
+            };
+        }
+        let r = (top_r * top_a + bot_r * bot_a * top_a_inv) / a;
+        let g = (top_g * top_a + bot_g * bot_a * top_a_inv) / a;
+        let b = (top_b * top_a + bot_b * bot_a * top_a_inv) / a;
+
+        Self { r, g, b, a }
+    }
+
+    pub fn approach(self, target: LinearColor, amount: f32) -> Self {
+        let amount = amount.clamp(0.0, 1.0);
+        Self {
+            r: self.r + (target.r - self.r) * amount,
+            g: self.g + (target.g - self.g) * amount,
+            b: self.b + (target.b - self.b) * amount,
+            a: self.a + (target.a - self.a) * amount,
+        }
+    }
+}
+
+impl OkLab {
+    // ignores the alpha
+    pub fn from_linear(color: LinearColor) -> Self {
+        let LinearColor { r, g, b, a: _ } = color;
+        let l = 0.41222147 * r + 0.53633254 * g + 0.051445993 * b;
+        let m = 0.2119035 * r + 0.6806995 * g + 0.10739696 * b;
+        let s = 0.08830246 * r + 0.28171884 * g + 0.6299787 * b;
+        let l = l.cbrt();
+        let m = m.cbrt();
+        let s = s.cbrt();
+        Self {
+            l: 0.21045426 * l + 0.7936178 * m - 0.004072047 * s,
+            a: 1.9779985 * l - 2.4285922 * m + 0.4505937 * s,
+            b: 0.025904037 * l + 0.78277177 * m - 0.80867577 * s,
+        }
+    }
+
+    // opaque, and not clamped to sRGB
+    pub fn to_linear(self) -> LinearColor {
+        let Self { l, a, b } = self;
+        let l_ = l + 0.39633778 * a + 0.21580376 * b;
+        let m_ = l - 0.105561346 * a - 0.06385417 * b;
+        let s_ = l - 0.08948418 * a - 1.2914855 * b;
+        let l = l_ * l_ * l_;
+        let m = m_ * m_ * m_;
+        let s = s_ * s_ * s_;
+        LinearColor {
+            r: 4.0767417 * l - 3.3077116 * m + 0.23096994 * s,
+            g: -1.268438 * l + 2.6097574 * m - 0.34131938 * s,
+            b: -0.0041960864 * l - 0.7034186 * m + 1.7076147 * s,
+            a: 1.0,
+        }
+    }
+
+    pub fn to_lch(self) -> OkLch {
+        let Self { l, a, b } = self;
+        OkLch {
+            l,
+            c: a.hypot(b),
+            h: b.atan2(a).to_degrees().rem_euclid(360.0),
+        }
+    }
+}
+
+impl OkLch {
+    pub fn to_lab(self) -> OkLab {
+        let Self { l, c, h } = self;
+        let (sin, cos) = h.to_radians().sin_cos();
+        OkLab {
+            l,
+            a: c * cos,
+            b: c * sin,
+        }
+    }
+
+    // reduces the chroma until the color fits in sRGB, which keeps the
+    // lightness and hue unlike clipping each channel
+    pub fn gamut_map(self) -> Self {
+        const EPSILON: f32 = 1e-4;
+        let in_gamut = |lch: OkLch| {
+            let LinearColor { r, g, b, a: _ } = lch.to_lab().to_linear();
+            [r, g, b]
+                .into_iter()
+                .all(|x| (-EPSILON..=1.0 + EPSILON).contains(&x))
+        };
+        let l = self.l.clamp(0.0, 1.0);
+        let lch = Self { l, ..self };
+        if in_gamut(lch) {
+            return lch;
+        }
+        let mut low = 0.0;
+        let mut high = lch.c;
+        for _ in 0..16 {
+            let c = (low + high) / 2.0;
+            if in_gamut(Self { c, ..lch }) {
+                low = c;
+            } else {
+                high = c;
+            }
+        }
+        Self { c: low, ..lch }
+    }
+}
+
+pub fn srgb_from_linear(x: f32) -> f32 {
+    if x <= 0.0031308 {
+        x * 12.92
+    } else {
+        x.powf(1.0 / 2.4) * 1.055 - 0.055
+    }
+}
+
+pub fn linear_from_srgb(x: f32) -> f32 {
+    if x <= 0.04045 {
+        x / 12.92
+    } else {
+        ((x + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_from_srgb_byte(x: u8) -> f32 {
+    static TABLE: OnceLock<[f32; 256]> = OnceLock::new();
+    let table = TABLE.get_or_init(|| {
+        std::array::from_fn(|x| linear_from_srgb(x as f32 / BYTE_MAX_FLOAT))
+    });
+    table[x as usize]
+}
+
 pub struct ColorHex(Color);
 
 impl fmt::Display for ColorHex {
@@ -0,0 +1,52 @@
+use crate::{
+    camera::Camera,
+    world::{DisplayParams, SimParams},
+};
+use base64::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub const CONFIG_VERSION: u64 = 1;
+
+pub const DEFAULT_FRAME_LIMIT: usize = 60 * 60;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Config {
+    pub version: u64,
+    pub sim_params: SimParams,
+    pub display_params: DisplayParams,
+    pub frame_limit: usize,
+    #[serde(default)]
+    pub camera: Camera,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            version: CONFIG_VERSION,
+            sim_params: SimParams::default(),
+            display_params: DisplayParams::default(),
+            frame_limit: DEFAULT_FRAME_LIMIT,
+            camera: Camera::default(),
+        }
+    }
+}
+
+pub fn encode_config_str(config: Config) -> String {
+    let message_pack = rmp_serde::to_vec(&config).unwrap();
+    let deflated_message_pack = deflate::deflate_bytes_conf(
+        message_pack.as_slice(),
+        deflate::CompressionOptions::high(),
+    );
+    let base64 =
+        BASE64_URL_SAFE_NO_PAD.encode(deflated_message_pack.as_slice());
+    base64
+}
+
+pub fn decode_config_str(s: &str) -> Option<Config> {
+    let base64 = s;
+    let deflated_message_pack = BASE64_URL_SAFE_NO_PAD.decode(base64).ok()?;
+    let message_pack =
+        inflate::inflate_bytes(deflated_message_pack.as_slice()).ok()?;
+    let config = rmp_serde::from_slice(message_pack.as_slice()).ok()?;
+    Some(config)
+}
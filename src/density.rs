@@ -0,0 +1,142 @@
+use crate::{
+    accumulator::{encode, encode_table},
+    color::{Color, LinearColor},
+    dither::Dither,
+    image::resize_centered,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DensityMap {
+    // in stops
+    pub exposure: f32,
+    pub gamma: f32,
+    // 0..1, how much the gamma is applied to the density alone instead of
+    // to each color channel
+    pub vibrancy: f32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bin {
+    count: f32,
+    r: f32,
+    g: f32,
+    b: f32,
+}
+
+// counts how often each pixel is visited along with the sum of the colors
+// visiting it, and maps the log of the density to brightness like fractal
+// flame renderers do, which keeps detail in both sparse and very dense areas
+pub struct Histogram {
+    width: usize,
+    height: usize,
+    density_map: DensityMap,
+    encode_table: Vec<f32>,
+    bins: Vec<Bin>,
+}
+
+const EMPTY_BIN: Bin = Bin {
+    count: 0.0,
+    r: 0.0,
+    g: 0.0,
+    b: 0.0,
+};
+
+impl Histogram {
+    pub fn new(width: usize, height: usize, density_map: DensityMap) -> Self {
+        Self {
+            width,
+            height,
+            density_map,
+            encode_table: encode_table(1.0),
+            bins: vec![EMPTY_BIN; width * height],
+        }
+    }
+
+    pub fn set_density_map(&mut self, density_map: DensityMap) {
+        self.density_map = density_map;
+    }
+
+    // translucent colors count as a fraction of a visit
+    pub fn add(&mut self, x: usize, y: usize, color: LinearColor, weight: f32) {
+        let weight = weight * color.a;
+        let bin = &mut self.bins[x + y * self.width];
+        bin.count += weight;
+        bin.r += color.r * weight;
+        bin.g += color.g * weight;
+        bin.b += color.b * weight;
+    }
+
+    pub fn fade(&mut self, amount: f32) {
+        let keep = 1.0 - amount.clamp(0.0, 1.0);
+        for bin in &mut self.bins {
+            bin.count *= keep;
+            bin.r *= keep;
+            bin.g *= keep;
+            bin.b *= keep;
+        }
+    }
+
+    pub fn resize(&mut self, width: usize, height: usize) {
+        self.bins = resize_centered(
+            &self.bins,
+            (self.width, self.height),
+            (width, height),
+            |_| EMPTY_BIN,
+        );
+        self.width = width;
+        self.height = height;
+    }
+
+    pub fn clear(&mut self) {
+        self.bins.fill(EMPTY_BIN);
+    }
+
+    pub fn tone_map(
+        &self,
+        out: &mut [Color],
+        background: &[Color],
+        dither: Dither,
+    ) {
+        let DensityMap {
+            exposure,
+            gamma,
+            vibrancy,
+        } = self.density_map;
+        let scale = exposure.exp2();
+        let max_count =
+            self.bins.iter().map(|bin| bin.count).fold(0.0, f32::max);
+        let log_max = (max_count * scale).ln_1p();
+        for (idx, ((bin, out), &background)) in
+            self.bins.iter().zip(out).zip(background).enumerate()
+        {
+            let x = idx % self.width;
+            let y = idx / self.width;
+            let encode = |c: f32| encode(&self.encode_table, c, dither, x, y);
+            if bin.count <= 0.0 || log_max <= 0.0 {
+                // the background is already bytes
+                *out = background;
+                continue;
+            }
+            let background = LinearColor::from_color(background);
+            let density = (bin.count * scale).ln_1p() / log_max;
+            let alpha = density.powf(1.0 / gamma);
+            let channel = |sum: f32, background: f32| {
+                let mean = sum / bin.count;
+                let vibrant = mean * alpha;
+                let flat = (mean * density).powf(1.0 / gamma);
+                let c = vibrancy * vibrant + (1.0 - vibrancy) * flat;
+                c + background * (1.0 - alpha)
+            };
+            *out = Color {
+                r: encode(channel(bin.r, background.r)),
+                g: encode(channel(bin.g, background.g)),
+                b: encode(channel(bin.b, background.b)),
+                a: dither.quantize(
+                    (alpha + background.a * (1.0 - alpha)) * 255.0,
+                    x,
+                    y,
+                ),
+            };
+        }
+    }
+}
@@ -0,0 +1,191 @@
+use rand::prelude::*;
+use rand_chacha::ChaCha20Rng;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+// tiles the image, a power of two so coordinates wrap with a mask
+const BLUE_NOISE_SIZE: usize = 64;
+// spread of the energy around each point when placing them, and how far out
+// it is still worth adding
+const BLUE_NOISE_SIGMA: f32 = 1.5;
+const BLUE_NOISE_RADIUS: isize = 7;
+// fraction of the points placed at random before they are spread out
+const BLUE_NOISE_INITIAL_FILL: f32 = 0.1;
+
+// how the high precision buffers and gradients are rounded to bytes, adding
+// a threshold that varies per pixel below a step turns banding into fine
+// noise
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize,
+)]
+pub enum Dither {
+    #[default]
+    None,
+    // regular 8x8 pattern
+    Bayer,
+    // irregular pattern without low frequencies, which is harder to notice
+    BlueNoise,
+}
+
+impl Dither {
+    pub const ALL: &[Self] = &[Self::None, Self::Bayer, Self::BlueNoise];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Bayer => "bayer",
+            Self::BlueNoise => "blue noise",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|dither| dither.name() == name)
+    }
+
+    // -0.5..0.5 of a step, averaging to 0 over the pattern
+    pub fn offset(self, x: usize, y: usize) -> f32 {
+        match self {
+            Self::None => 0.0,
+            Self::Bayer => (bayer(x, y, 3) as f32 + 0.5) / 64.0 - 0.5,
+            Self::BlueNoise => {
+                let mask = BLUE_NOISE_SIZE - 1;
+                blue_noise()[(x & mask) + (y & mask) * BLUE_NOISE_SIZE]
+            },
+        }
+    }
+
+    // a value in 0..255 rounded to a byte, the same as plain rounding
+    // without dithering
+    pub fn quantize(self, value: f32, x: usize, y: usize) -> u8 {
+        (value + self.offset(x, y)).round().clamp(0.0, 255.0) as u8
+    }
+}
+
+// the index of a pixel in the 2^bits square Bayer matrix, the low bits of the
+// coordinates pick the high bits of the index so neighbours are far apart
+fn bayer(x: usize, y: usize, bits: u32) -> usize {
+    (0..bits).fold(0, |index, bit| {
+        let x = (x >> bit) & 1;
+        let y = (y >> bit) & 1;
+        (index << 2) | ((x ^ y) << 1) | y
+    })
+}
+
+// Ulichney's void and cluster method, the points are ranked by always adding
+// the next one where there is the most room, so thresholding at any level
+// gives evenly spread pixels
+fn blue_noise() -> &'static [f32] {
+    static TABLE: OnceLock<Vec<f32>> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let len = BLUE_NOISE_SIZE * BLUE_NOISE_SIZE;
+        let mut pattern = BluePattern::new();
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let initial_count = (len as f32 * BLUE_NOISE_INITIAL_FILL) as usize;
+        while pattern.count < initial_count {
+            let idx = rng.gen_range(0..len);
+            if !pattern.points[idx] {
+                pattern.toggle(idx);
+            }
+        }
+        // move the tightest point into the biggest gap until that settles
+        for _ in 0..len {
+            let cluster = pattern.tightest_cluster();
+            pattern.toggle(cluster);
+            let void = pattern.largest_void();
+            pattern.toggle(void);
+            if void == cluster {
+                break;
+            }
+        }
+
+        let mut ranks = vec![0; len];
+        let initial = pattern.clone();
+        while pattern.count > 0 {
+            let cluster = pattern.tightest_cluster();
+            pattern.toggle(cluster);
+            ranks[cluster] = pattern.count;
+        }
+        let mut pattern = initial;
+        while pattern.count < len {
+            let void = pattern.largest_void();
+            ranks[void] = pattern.count;
+            pattern.toggle(void);
+        }
+
+        ranks
+            .into_iter()
+            .map(|rank| (rank as f32 + 0.5) / len as f32 - 0.5)
+            .collect()
+    })
+}
+
+#[derive(Clone)]
+struct BluePattern {
+    points: Vec<bool>,
+    // sum of a gaussian around every point, wrapping around the edges
+    energy: Vec<f32>,
+    count: usize,
+}
+
+impl BluePattern {
+    fn new() -> Self {
+        let len = BLUE_NOISE_SIZE * BLUE_NOISE_SIZE;
+        Self {
+            points: vec![false; len],
+            energy: vec![0.0; len],
+            count: 0,
+        }
+    }
+
+    fn toggle(&mut self, idx: usize) {
+        let sign = if self.points[idx] { -1.0 } else { 1.0 };
+        self.points[idx] = !self.points[idx];
+        if self.points[idx] {
+            self.count += 1;
+        } else {
+            self.count -= 1;
+        }
+        let size = BLUE_NOISE_SIZE as isize;
+        let x = (idx % BLUE_NOISE_SIZE) as isize;
+        let y = (idx / BLUE_NOISE_SIZE) as isize;
+        for dy in -BLUE_NOISE_RADIUS..=BLUE_NOISE_RADIUS {
+            let py = (y + dy).rem_euclid(size) as usize;
+            for dx in -BLUE_NOISE_RADIUS..=BLUE_NOISE_RADIUS {
+                let px = (x + dx).rem_euclid(size) as usize;
+                let d_sq = (dx * dx + dy * dy) as f32;
+                self.energy[px + py * BLUE_NOISE_SIZE] += sign
+                    * (-d_sq / (2.0 * BLUE_NOISE_SIGMA * BLUE_NOISE_SIGMA))
+                        .exp();
+            }
+        }
+    }
+
+    fn tightest_cluster(&self) -> usize {
+        self.extreme(true, |a, b| a > b)
+    }
+
+    fn largest_void(&self) -> usize {
+        self.extreme(false, |a, b| a < b)
+    }
+
+    // the first of the points or gaps with the most extreme energy
+    fn extreme(&self, point: bool, better: impl Fn(f32, f32) -> bool) -> usize {
+        let mut best = None;
+        for (idx, (&is_point, &energy)) in
+            self.points.iter().zip(&self.energy).enumerate()
+        {
+            if is_point != point {
+                continue;
+            }
+            if best
+                .map_or(true, |(_idx, best_energy)| better(energy, best_energy))
+            {
+                best = Some((idx, energy));
+            }
+        }
+        best.map_or(0, |(idx, _energy)| idx)
+    }
+}
@@ -0,0 +1,197 @@
+use crate::{
+    camera::Camera,
+    image::Image,
+    world::{DisplayParams, Seed, SimParams, World},
+};
+use anyhow::Result;
+
+const COVERAGE_WEIGHT: f32 = 0.4;
+const ENTROPY_WEIGHT: f32 = 0.4;
+const SPREAD_WEIGHT: f32 = 0.2;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SeedScore {
+    pub score: f32,
+    pub coverage: f32,
+    pub entropy: f32,
+    pub spread: f32,
+}
+
+pub struct SeedResult {
+    pub seed: Seed,
+    pub score: SeedScore,
+    pub image: Image,
+}
+
+pub struct ExploreParams {
+    pub frame_count: usize,
+    pub width: usize,
+    pub height: usize,
+    pub camera: Camera,
+}
+
+// runs the simulation headlessly and renders it the same way the canvas would,
+// so the score reflects what would actually be seen
+pub fn explore_seed(
+    sim_params: &SimParams,
+    display_params: &DisplayParams,
+    explore_params: &ExploreParams,
+    seed: Seed,
+) -> Result<SeedResult> {
+    let mut explorer =
+        SeedExplorer::new(sim_params, display_params, explore_params, seed)?;
+    explorer.step(usize::MAX);
+    Ok(explorer.finish())
+}
+
+// explores a single seed a few frames at a time, so the web UI can yield to the
+// page in between
+pub struct SeedExplorer {
+    seed: Seed,
+    world: World,
+    image: Image,
+    camera: Camera,
+    trail_fade_amount: f32,
+    frames_left: usize,
+}
+
+impl SeedExplorer {
+    pub fn new(
+        sim_params: &SimParams,
+        display_params: &DisplayParams,
+        explore_params: &ExploreParams,
+        seed: Seed,
+    ) -> Result<Self> {
+        let ExploreParams {
+            frame_count,
+            width,
+            height,
+            camera,
+        } = explore_params;
+
+        let sim_params = SimParams {
+            seed: seed.clone(),
+            ..sim_params.clone()
+        };
+        let world = World::new(&sim_params, display_params)?;
+        let mut image = Image::new(*width, *height, display_params.background);
+        display_params.configure_image(&mut image);
+        let mut camera = camera.clone();
+        world.render_step(&mut image, &mut camera, 0.0);
+        Ok(Self {
+            seed,
+            world,
+            image,
+            camera,
+            trail_fade_amount: display_params.trail_fade_amount(),
+            frames_left: *frame_count,
+        })
+    }
+
+    // runs up to frame_count more frames and returns whether it's done
+    pub fn step(&mut self, frame_count: usize) -> bool {
+        let Self {
+            seed: _,
+            world,
+            image,
+            camera,
+            trail_fade_amount,
+            frames_left,
+        } = self;
+
+        let frame_count = frame_count.min(*frames_left);
+        for _ in 0..frame_count {
+            world.update();
+            world.render_step(image, camera, *trail_fade_amount);
+        }
+        *frames_left -= frame_count;
+        *frames_left == 0
+    }
+
+    pub fn finish(self) -> SeedResult {
+        let Self {
+            seed,
+            world: _,
+            mut image,
+            camera: _,
+            trail_fade_amount: _,
+            frames_left: _,
+        } = self;
+
+        image.tone_map();
+        let score = SeedScore::new(&image);
+        SeedResult { seed, score, image }
+    }
+}
+
+impl SeedScore {
+    pub fn new(image: &Image) -> Self {
+        let width = image.width();
+        let pixel_count = image.pixels().len();
+
+        let mut covered_count = 0usize;
+        let mut luminance_counts = [0usize; 256];
+        let mut bounds = None::<(usize, usize, usize, usize)>;
+        for (idx, (&pixel, &background)) in image
+            .pixels()
+            .iter()
+            .zip(image.background_pixels())
+            .enumerate()
+        {
+            if pixel == background {
+                continue;
+            }
+            covered_count += 1;
+            let luminance = (pixel.luminance() * 255.0).round() as usize;
+            luminance_counts[luminance.min(255)] += 1;
+            let x = idx % width;
+            let y = idx / width;
+            bounds = Some(bounds.map_or(
+                (x, y, x, y),
+                |(min_x, min_y, max_x, max_y)| {
+                    (x.min(min_x), y.min(min_y), x.max(max_x), y.max(max_y))
+                },
+            ));
+        }
+
+        // fraction of the image touched by any trail
+        let coverage = if pixel_count == 0 {
+            0.0
+        } else {
+            covered_count as f32 / pixel_count as f32
+        };
+
+        // entropy of the trail brightness, normalized to 0..1
+        let entropy = if covered_count == 0 {
+            0.0
+        } else {
+            let covered_count = covered_count as f32;
+            -luminance_counts
+                .iter()
+                .filter(|&&count| count > 0)
+                .map(|&count| {
+                    let p = count as f32 / covered_count;
+                    p * p.log2()
+                })
+                .sum::<f32>()
+                / 8.0
+        };
+
+        // fraction of the image inside the bounding box of the trails
+        let spread = bounds.map_or(0.0, |(min_x, min_y, max_x, max_y)| {
+            ((max_x - min_x + 1) * (max_y - min_y + 1)) as f32
+                / pixel_count as f32
+        });
+
+        let score = COVERAGE_WEIGHT * coverage
+            + ENTROPY_WEIGHT * entropy
+            + SPREAD_WEIGHT * spread;
+
+        Self {
+            score,
+            coverage,
+            entropy,
+            spread,
+        }
+    }
+}
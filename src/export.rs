@@ -0,0 +1,216 @@
+use crate::{
+    camera::Camera,
+    color::{Color, LinearColor},
+    image::{Image, RenderMode},
+    world::{DisplayParams, SimParams, World},
+};
+use anyhow::{ensure, Result};
+use serde::{Deserialize, Serialize};
+
+// wasm can address 4 GiB, but browsers don't always give out that much
+const MAX_EXPORT_BYTES: usize = 1 << 30;
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize,
+)]
+pub enum ExportSource {
+    // only the frames still kept in the world's history
+    #[default]
+    History,
+    // the whole run, simulated again from the start
+    Simulate,
+}
+
+pub struct ExportParams {
+    // frames to simulate when re-simulating
+    pub frame_count: usize,
+    // size of the view being exported, which the camera was set up for
+    pub view_width: usize,
+    pub view_height: usize,
+    pub width: usize,
+    pub height: usize,
+    pub camera: Camera,
+    pub source: ExportSource,
+}
+
+impl ExportSource {
+    pub const ALL: &[Self] = &[Self::History, Self::Simulate];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::History => "history",
+            Self::Simulate => "simulate",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|source| source.name() == name)
+    }
+}
+
+// renders the run offscreen at another size, showing the same part of the
+// world as the view with everything scaled up to match
+pub fn render_export(
+    world: &World,
+    sim_params: &SimParams,
+    display_params: &DisplayParams,
+    export_params: &ExportParams,
+) -> Result<Image> {
+    let mut export =
+        Export::new(world, sim_params, display_params, export_params)?;
+    export.step(usize::MAX);
+    Ok(export.finish())
+}
+
+// the most pixels an export can have with the display params, keeping the
+// image and its buffers well within what wasm can address
+pub fn max_export_pixels(display_params: &DisplayParams) -> usize {
+    // the pixels, the background, and the PNG they're encoded to
+    let image = 3 * size_of::<Color>();
+    let buffer = match display_params.render_mode {
+        // only faded trails keep the remainders of their fades
+        RenderMode::Direct if display_params.trail_fade == 0.0 => 0,
+        // a float per channel, or a count and the sums of the channels
+        RenderMode::Direct | RenderMode::Accumulate | RenderMode::Density => {
+            size_of::<LinearColor>()
+        },
+    };
+    MAX_EXPORT_BYTES / (image + buffer)
+}
+
+// an export rendered a few frames at a time, so the web UI can yield to the
+// page in between
+pub struct Export {
+    // a copy when rendering the history, since the live world moves on
+    world: World,
+    image: Image,
+    camera: Camera,
+    trail_fade_amount: f32,
+    source: ExportSource,
+    frame_idx: usize,
+    frame_count: usize,
+}
+
+impl Export {
+    pub fn new(
+        world: &World,
+        sim_params: &SimParams,
+        display_params: &DisplayParams,
+        export_params: &ExportParams,
+    ) -> Result<Self> {
+        let ExportParams {
+            frame_count,
+            view_width,
+            view_height,
+            width,
+            height,
+            camera,
+            source,
+        } = export_params;
+        ensure!(
+            *width > 0 && *height > 0 && *view_width > 0 && *view_height > 0,
+            "export size must not be empty"
+        );
+        let max_pixels = max_export_pixels(display_params);
+        ensure!(
+            width.saturating_mul(*height) <= max_pixels,
+            "export is too big, {} rendering is limited to {} megapixels",
+            display_params.render_mode.name(),
+            max_pixels / 1_000_000
+        );
+
+        let scale = (*width as f32 / *view_width as f32)
+            .min(*height as f32 / *view_height as f32);
+        let mut camera = Camera {
+            zoom: camera.zoom * scale,
+            ..camera.clone()
+        };
+        let display_params = DisplayParams {
+            // a plain particle covers about one pixel, so treat it as a brush
+            // of half a pixel when scaling
+            brush_radius: if scale == 1.0 {
+                display_params.brush_radius
+            } else {
+                ((display_params.brush_radius + 0.5) * scale - 0.5).max(0.0)
+            },
+            ..display_params.clone()
+        };
+        let trail_fade_amount = display_params.trail_fade_amount();
+
+        let mut image = Image::new(*width, *height, display_params.background);
+        display_params.configure_image(&mut image);
+        let (world, frame_count) = match source {
+            ExportSource::History => {
+                camera.track(world, *width, *height);
+                (world.clone(), world.history().len())
+            },
+            ExportSource::Simulate => {
+                let world = World::new(sim_params, &display_params)?;
+                world.render_step(&mut image, &mut camera, 0.0);
+                (world, *frame_count)
+            },
+        };
+        Ok(Self {
+            world,
+            image,
+            camera,
+            trail_fade_amount,
+            source: *source,
+            frame_idx: 0,
+            frame_count,
+        })
+    }
+
+    // renders up to frame_count more frames and returns whether it's done
+    pub fn step(&mut self, frame_count: usize) -> bool {
+        let Self {
+            world,
+            image,
+            camera,
+            trail_fade_amount,
+            source,
+            frame_idx,
+            frame_count: total_frame_count,
+        } = self;
+
+        let end = frame_idx
+            .saturating_add(frame_count)
+            .min(*total_frame_count);
+        match source {
+            ExportSource::History => {
+                world.render_history_frames(
+                    image,
+                    camera,
+                    *trail_fade_amount,
+                    *frame_idx..end,
+                );
+            },
+            ExportSource::Simulate => {
+                for _ in *frame_idx..end {
+                    world.update();
+                    world.render_step(image, camera, *trail_fade_amount);
+                }
+            },
+        }
+        *frame_idx = end;
+        *frame_idx == *total_frame_count
+    }
+
+    pub fn finish(self) -> Image {
+        let Self {
+            world: _,
+            mut image,
+            camera: _,
+            trail_fade_amount: _,
+            source: _,
+            frame_idx: _,
+            frame_count: _,
+        } = self;
+
+        image.tone_map();
+        image
+    }
+}
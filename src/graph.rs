@@ -0,0 +1,489 @@
+use crate::color::Color;
+use anyhow::{bail, ensure, Context, Result};
+
+// imported graphs are checked against this before anything is allocated for
+// them, so a typo in an index can't take down the page
+pub const MAX_PARTICLE_COUNT: usize = 1000000;
+
+// longer cycles are not searched for, so graphs without any short cycles don't
+// get a full breadth-first search from every node
+const SHORTEST_CYCLE_SEARCH_DEPTH_LIMIT: usize = 64;
+
+pub struct PartnerGraph<'a> {
+    partners: &'a [[usize; 2]],
+    colors: &'a [Color],
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphAnalysis {
+    pub node_count: usize,
+    pub edge_count: usize,
+    pub component_count: usize,
+    pub largest_component_size: usize,
+    pub cyclic_component_count: usize,
+    pub cyclic_node_count: usize,
+    pub mutual_pair_count: usize,
+    pub shortest_cycle: Option<usize>,
+    pub in_degree_counts: Vec<usize>,
+    pub leaders: Vec<usize>,
+}
+
+impl<'a> PartnerGraph<'a> {
+    pub fn new(partners: &'a [[usize; 2]], colors: &'a [Color]) -> Self {
+        Self { partners, colors }
+    }
+
+    pub fn analyze(&self) -> GraphAnalysis {
+        let Self {
+            partners,
+            colors: _,
+        } = self;
+
+        let node_count = partners.len();
+        let edge_count = node_count * 2;
+
+        let mut in_degrees = vec![0usize; node_count];
+        for &[j, k] in partners.iter() {
+            in_degrees[j] += 1;
+            in_degrees[k] += 1;
+        }
+        let max_in_degree = in_degrees.iter().copied().max().unwrap_or(0);
+        let mut in_degree_counts = vec![0usize; max_in_degree + 1];
+        for &in_degree in &in_degrees {
+            in_degree_counts[in_degree] += 1;
+        }
+        let leaders = (0..node_count)
+            .filter(|&idx| in_degrees[idx] == 0)
+            .collect::<Vec<_>>();
+
+        let (component_count, components) = self.components();
+        let mut component_sizes = vec![0usize; component_count];
+        for &component in &components {
+            component_sizes[component] += 1;
+        }
+        let largest_component_size =
+            component_sizes.iter().copied().max().unwrap_or(0);
+        // every particle follows two others, so there are no self loops and
+        // only multi-node components can contain cycles
+        let cyclic_component_count =
+            component_sizes.iter().filter(|&&size| size > 1).count();
+        let cyclic_node_count = component_sizes
+            .iter()
+            .filter(|&&size| size > 1)
+            .sum::<usize>();
+
+        let mutual_pair_count = partners
+            .iter()
+            .enumerate()
+            .flat_map(|(i, p)| p.iter().map(move |&j| (i, j)))
+            .filter(|&(i, j)| i < j && partners[j].contains(&i))
+            .count();
+
+        let shortest_cycle = if mutual_pair_count > 0 {
+            Some(2)
+        } else {
+            self.shortest_cycle(&components)
+        };
+
+        GraphAnalysis {
+            node_count,
+            edge_count,
+            component_count,
+            largest_component_size,
+            cyclic_component_count,
+            cyclic_node_count,
+            mutual_pair_count,
+            shortest_cycle,
+            in_degree_counts,
+            leaders,
+        }
+    }
+
+    // strongly connected components using an iterative version of Tarjan's
+    // algorithm, since the recursive one would overflow the stack for large
+    // particle counts, returns the number of components and the component of
+    // each particle
+    pub fn components(&self) -> (usize, Vec<usize>) {
+        let Self {
+            partners,
+            colors: _,
+        } = self;
+
+        const UNVISITED: usize = usize::MAX;
+
+        let node_count = partners.len();
+        let mut index = vec![UNVISITED; node_count];
+        let mut low_link = vec![0usize; node_count];
+        let mut on_stack = vec![false; node_count];
+        let mut stack = Vec::new();
+        let mut components = vec![0usize; node_count];
+        let mut component_count = 0;
+        let mut next_index = 0;
+        let mut call_stack = Vec::<(usize, usize)>::new();
+
+        for root in 0..node_count {
+            if index[root] != UNVISITED {
+                continue;
+            }
+            call_stack.push((root, 0));
+            while let Some(&mut (node, ref mut edge)) = call_stack.last_mut() {
+                if *edge == 0 {
+                    index[node] = next_index;
+                    low_link[node] = next_index;
+                    next_index += 1;
+                    stack.push(node);
+                    on_stack[node] = true;
+                }
+                if let Some(&next) = partners[node].get(*edge) {
+                    *edge += 1;
+                    if index[next] == UNVISITED {
+                        call_stack.push((next, 0));
+                    } else if on_stack[next] {
+                        low_link[node] = low_link[node].min(index[next]);
+                    }
+                    continue;
+                }
+                call_stack.pop();
+                if let Some(&(parent, _)) = call_stack.last() {
+                    low_link[parent] = low_link[parent].min(low_link[node]);
+                }
+                if low_link[node] == index[node] {
+                    loop {
+                        let member = stack.pop().unwrap();
+                        on_stack[member] = false;
+                        components[member] = component_count;
+                        if member == node {
+                            break;
+                        }
+                    }
+                    component_count += 1;
+                }
+            }
+        }
+
+        (component_count, components)
+    }
+
+    // girth of the graph, found by a breadth-first search from every node
+    // that stops as soon as it can't beat the shortest cycle found so far
+    fn shortest_cycle(&self, components: &[usize]) -> Option<usize> {
+        let Self {
+            partners,
+            colors: _,
+        } = self;
+
+        let node_count = partners.len();
+        let mut best = SHORTEST_CYCLE_SEARCH_DEPTH_LIMIT + 1;
+        let mut visited = vec![usize::MAX; node_count];
+        let mut frontier = Vec::new();
+        let mut next_frontier = Vec::new();
+        for start in 0..node_count {
+            visited[start] = start;
+            frontier.clear();
+            frontier.push(start);
+            let mut depth = 0;
+            'search: while !frontier.is_empty() && depth + 1 < best {
+                depth += 1;
+                next_frontier.clear();
+                for &node in &frontier {
+                    for &next in &partners[node] {
+                        // cycles never leave a strongly connected component
+                        if components[next] != components[start] {
+                            continue;
+                        }
+                        if next == start {
+                            best = depth;
+                            break 'search;
+                        }
+                        if visited[next] != start {
+                            visited[next] = start;
+                            next_frontier.push(next);
+                        }
+                    }
+                }
+                std::mem::swap(&mut frontier, &mut next_frontier);
+            }
+        }
+        (best <= SHORTEST_CYCLE_SEARCH_DEPTH_LIMIT).then_some(best)
+    }
+
+    pub fn generate_dot(&self) -> String {
+        use std::fmt::Write;
+
+        let Self { partners, colors } = self;
+
+        let mut s = String::new();
+
+        macro_rules! w {
+            ($($args:tt)*) => (write!(&mut s, $($args)*).unwrap())
+        }
+        macro_rules! wln {
+            ($($args:tt)*) => (writeln!(&mut s, $($args)*).unwrap())
+        }
+
+        wln!(r#"digraph partners {{"#);
+        wln!(r#"  node [shape="point"];"#);
+        for (idx, color) in colors.iter().enumerate() {
+            let color = color.fmt_hex();
+            wln!(r##"  {idx} [color="#{color}"];"##);
+        }
+        for (idx, [p1, p2]) in partners.iter().enumerate() {
+            w!(r#"  {idx} -> {p1} [label="1"];"#);
+            wln!(r#" {idx} -> {p2} [label="2"];"#);
+        }
+        wln!(r#"}}"#);
+
+        s
+    }
+
+    pub fn generate_graphml(&self) -> String {
+        use std::fmt::Write;
+
+        let Self { partners, colors } = self;
+
+        let mut s = String::new();
+
+        macro_rules! w {
+            ($($args:tt)*) => (write!(&mut s, $($args)*).unwrap())
+        }
+        macro_rules! wln {
+            ($($args:tt)*) => (writeln!(&mut s, $($args)*).unwrap())
+        }
+
+        wln!(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+        w!(r#"<graphml"#);
+        w!(r#" xmlns="http://graphml.graphdrawing.org/xmlns""#);
+        w!(r#" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance""#);
+        w!(r#" xsi:schemaLocation="http://graphml.graphdrawing.org/xmlns"#);
+        w!(r#" http://graphml.graphdrawing.org/xmlns/1.0/graphml.xsd""#);
+        wln!(r#">"#);
+        w!(r#"  <key id="color" for="node""#);
+        wln!(r#" attr.name="color" attr.type="string" />"#);
+        w!(r#"  <key id="partner" for="edge""#);
+        wln!(r#" attr.name="partner" attr.type="int" />"#);
+        wln!(r#"  <graph id="partners" edgedefault="directed">"#);
+        for (idx, color) in colors.iter().enumerate() {
+            let color = color.fmt_hex();
+            w!(r#"    <node id="n{idx}">"#);
+            w!(r##"<data key="color">#{color}</data>"##);
+            wln!(r#"</node>"#);
+        }
+        for (idx, partners) in partners.iter().enumerate() {
+            for (partner, p) in partners.iter().enumerate() {
+                let partner = partner + 1;
+                w!(r#"    <edge source="n{idx}" target="n{p}">"#);
+                w!(r#"<data key="partner">{partner}</data>"#);
+                wln!(r#"</edge>"#);
+            }
+        }
+        wln!(r#"  </graph>"#);
+        wln!(r#"</graphml>"#);
+
+        s
+    }
+}
+
+pub fn validate_partners(partners: &[[usize; 2]]) -> Result<()> {
+    let particle_count = partners.len();
+    ensure!(
+        particle_count >= 3,
+        "partner graph must have at least 3 particles, found {particle_count}"
+    );
+    for (idx, &[p1, p2]) in partners.iter().enumerate() {
+        ensure!(
+            p1 < particle_count && p2 < particle_count,
+            "partners of particle {idx} must be less than {particle_count}, \
+             found {p1} and {p2}"
+        );
+        ensure!(
+            p1 != idx && p2 != idx,
+            "particle {idx} can't be its own partner"
+        );
+        ensure!(p1 != p2, "partners of particle {idx} must be distinct");
+    }
+    Ok(())
+}
+
+// either CSV lines of `i,j,k` or Graphviz DOT edges `i -> j`, in the format
+// written by `PartnerGraph::generate_dot`, told apart by the `digraph` header
+pub fn parse_partners(s: &str) -> Result<Vec<[usize; 2]>> {
+    let mut tokens = dot_tokens(s);
+    let is_dot = tokens.next().is_some_and(|token| {
+        token.eq_ignore_ascii_case("digraph")
+            || token.eq_ignore_ascii_case("strict")
+                && tokens
+                    .next()
+                    .is_some_and(|token| token.eq_ignore_ascii_case("digraph"))
+    });
+    let partners = if is_dot {
+        parse_partners_dot(s)?
+    } else {
+        parse_partners_csv(s)?
+    };
+    validate_partners(&partners)?;
+    Ok(partners)
+}
+
+fn parse_partners_csv(s: &str) -> Result<Vec<[usize; 2]>> {
+    let mut partners = Vec::<Option<[usize; 2]>>::new();
+    let mut first_row = true;
+    for (line_idx, line) in s.lines().enumerate() {
+        let line_no = line_idx + 1;
+        let line = line.split('#').next().unwrap().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let header_allowed = std::mem::take(&mut first_row);
+        let fields = line
+            .split(',')
+            .map(|field| field.trim().parse::<usize>())
+            .collect::<Result<Vec<_>, _>>();
+        let [i, j, k] = match fields.as_deref() {
+            Ok(&[i, j, k]) => [i, j, k],
+            // allow a header row
+            Err(_) if header_allowed => continue,
+            _ => bail!("line {line_no}: expected `i,j,k`, found {line:?}"),
+        };
+        ensure!(
+            i < MAX_PARTICLE_COUNT,
+            "line {line_no}: particle {i} is past the limit of \
+             {MAX_PARTICLE_COUNT} particles"
+        );
+        if i >= partners.len() {
+            partners.resize(i + 1, None);
+        }
+        ensure!(
+            partners[i].is_none(),
+            "line {line_no}: partners of particle {i} are already defined"
+        );
+        partners[i] = Some([j, k]);
+    }
+    collect_partners(partners)
+}
+
+fn parse_partners_dot(s: &str) -> Result<Vec<[usize; 2]>> {
+    let tokens = dot_tokens(s).collect::<Vec<_>>();
+    let mut partners = Vec::<[Option<usize>; 2]>::new();
+    let mut token_idx = 0;
+    while token_idx < tokens.len() {
+        if tokens.get(token_idx + 1) != Some(&"->") {
+            token_idx += 1;
+            continue;
+        }
+        let mut nodes = vec![tokens[token_idx]];
+        token_idx += 1;
+        while tokens.get(token_idx) == Some(&"->") {
+            let node = tokens
+                .get(token_idx + 1)
+                .context("edge is missing its target")?;
+            nodes.push(node);
+            token_idx += 2;
+        }
+        // edges labeled by `generate_dot` keep their partner slot, otherwise
+        // partners are assigned in the order their edges appear
+        let mut slot = None;
+        if tokens.get(token_idx) == Some(&"[") {
+            while let Some(&token) = tokens.get(token_idx) {
+                token_idx += 1;
+                match token {
+                    "]" => break,
+                    "label" if tokens.get(token_idx) == Some(&"=") => {
+                        slot = match tokens.get(token_idx + 1) {
+                            Some(&"1") => Some(0),
+                            Some(&"2") => Some(1),
+                            _ => None,
+                        };
+                    },
+                    _ => {},
+                }
+            }
+        }
+        let parse_node = |node: &str| {
+            node.strip_prefix('n')
+                .unwrap_or(node)
+                .parse::<usize>()
+                .with_context(|| format!("invalid node {node:?}"))
+        };
+        for edge in nodes.windows(2) {
+            let i = parse_node(edge[0])?;
+            let j = parse_node(edge[1])?;
+            ensure!(
+                i < MAX_PARTICLE_COUNT,
+                "particle {i} is past the limit of {MAX_PARTICLE_COUNT} \
+                 particles"
+            );
+            if i >= partners.len() {
+                partners.resize(i + 1, [None, None]);
+            }
+            let node_partners = &mut partners[i];
+            let slot = slot
+                .or_else(|| node_partners.iter().position(Option::is_none))
+                .with_context(|| {
+                    format!("particle {i} has more than two partners")
+                })?;
+            ensure!(
+                node_partners[slot].is_none(),
+                "partner {} of particle {i} is defined more than once",
+                slot + 1
+            );
+            node_partners[slot] = Some(j);
+        }
+    }
+    collect_partners(
+        partners
+            .into_iter()
+            .map(|node_partners| match node_partners {
+                [Some(j), Some(k)] => Some([j, k]),
+                _ => None,
+            })
+            .collect(),
+    )
+}
+
+// just enough of the DOT language to find edges and their labels, with quotes
+// stripped from quoted IDs
+fn dot_tokens(s: &str) -> impl Iterator<Item = &str> {
+    let mut rest = s;
+    std::iter::from_fn(move || loop {
+        rest = rest.trim_start();
+        let (token, len) = if rest.is_empty() {
+            return None;
+        } else if rest.starts_with("//") || rest.starts_with('#') {
+            (None, rest.find('\n').unwrap_or(rest.len()))
+        } else if let Some(comment) = rest.strip_prefix("/*") {
+            (None, comment.find("*/").map_or(rest.len(), |end| end + 4))
+        } else if rest.starts_with("->") {
+            (Some("->"), 2)
+        } else if let Some(quoted) = rest.strip_prefix('"') {
+            let end = quoted.find('"').unwrap_or(quoted.len());
+            (Some(&quoted[..end]), (end + 2).min(rest.len()))
+        } else {
+            let end = match rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.'))
+            {
+                Some(0) => rest.chars().next().unwrap().len_utf8(),
+                Some(end) => end,
+                None => rest.len(),
+            };
+            (Some(&rest[..end]), end)
+        };
+        rest = &rest[len..];
+        if token.is_some() {
+            return token;
+        }
+    })
+}
+
+fn collect_partners(
+    partners: Vec<Option<[usize; 2]>>,
+) -> Result<Vec<[usize; 2]>> {
+    partners
+        .into_iter()
+        .enumerate()
+        .map(|(idx, partners)| {
+            partners.with_context(|| {
+                format!("particle {idx} does not have two partners")
+            })
+        })
+        .collect()
+}
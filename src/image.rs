@@ -1,20 +1,241 @@
-use crate::color::Color;
+use crate::{
+    accumulator::{Accumulator, ToneMap},
+    background::Background,
+    color::{BlendMode, BlendSpace, Color, LinearColor},
+    density::{DensityMap, Histogram},
+    dither::Dither,
+};
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::{cell::RefCell, io::Write, rc::Rc};
 use zerocopy::IntoBytes;
 
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize,
+)]
+pub enum RenderMode {
+    #[default]
+    Direct,
+    Accumulate,
+    Density,
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize,
+)]
+pub enum TrailStyle {
+    #[default]
+    Dots,
+    Segments,
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize,
+)]
+pub enum BrushFalloff {
+    #[default]
+    Gaussian,
+    Disc,
+    Square,
+}
+
+// the gaussian is scaled so the smallest one adds up to about a single pixel,
+// like a dot, instead of jumping to more than half again as bright
+const GAUSSIAN_PEAK: f32 = std::f32::consts::FRAC_2_PI;
+
+// a radius of zero is the bilinear splat of a single pixel
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Brush {
+    pub radius: f32,
+    pub falloff: BrushFalloff,
+    // how much the radius grows per pixel per frame of speed
+    pub speed_size: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Render {
+    Direct,
+    Accumulate(ToneMap),
+    Density(DensityMap),
+}
+
 pub struct Image {
     width: usize,
     height: usize,
-    background: Color,
+    background: Background,
+    blend_mode: BlendMode,
+    blend_space: BlendSpace,
+    trail_style: TrailStyle,
+    brush: Brush,
+    dither: Dither,
     pixels: Vec<Color>,
+    // the background of each pixel, which gradients make vary
+    background_pixels: Vec<Color>,
+    buffer: Buffer,
+    // reused between segments to merge their coverage per pixel
+    coverage: Vec<(usize, f32)>,
+}
+
+pub struct PngEncoder {
+    writer: png::StreamWriter<'static, PngBuffer>,
+    png: PngBuffer,
+    // the next row to encode
+    row: usize,
+}
+
+// the PNG being written, shared with the writer since it only gives it back
+// by dropping it
+#[derive(Clone, Default)]
+struct PngBuffer(Rc<RefCell<Vec<u8>>>);
+
+// where particles are drawn, the pixels are tone mapped from the other
+// buffers
+enum Buffer {
+    // the part of a step each channel has faded but not yet moved, empty
+    // until the first fade
+    Direct(Vec<[f32; 4]>),
+    Accumulate(Accumulator),
+    Density(Histogram),
+}
+
+// the two pixels a coordinate is split between and how much of it each gets,
+// either can be off the edge of the image
+macro_rules! calc {
+    ($x:expr, $w:expr) => {{
+        let w = $w;
+        let mut x = $x;
+        let wf = w as f32;
+        x -= 0.5;
+        if x <= -1.0 || x >= wf {
+            (None, None)
+        } else if x < 0.0 {
+            let xf = 1.0 + x;
+            (None, Some((0, xf)))
+        } else if x >= wf - 1.0 {
+            let xf = x.fract();
+            (Some((w - 1, (1.0 - xf))), None)
+        } else {
+            let xf = x.fract();
+            let x = x as usize;
+            (Some((x, (1.0 - xf))), Some((x + 1, xf)))
+        }
+    }};
+}
+
+impl RenderMode {
+    pub const ALL: &[Self] = &[Self::Direct, Self::Accumulate, Self::Density];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Direct => "direct",
+            Self::Accumulate => "accumulate",
+            Self::Density => "density",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|mode| mode.name() == name)
+    }
+}
+
+impl TrailStyle {
+    pub const ALL: &[Self] = &[Self::Dots, Self::Segments];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Dots => "dots",
+            Self::Segments => "segments",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|style| style.name() == name)
+    }
+}
+
+impl BrushFalloff {
+    pub const ALL: &[Self] = &[Self::Gaussian, Self::Disc, Self::Square];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Gaussian => "gaussian",
+            Self::Disc => "disc",
+            Self::Square => "square",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|falloff| falloff.name() == name)
+    }
+
+    // never too small to reach the nearest pixel center
+    fn gaussian_sigma(radius: f32) -> f32 {
+        (radius / 2.0).max(0.5)
+    }
+
+    // how far from the center the brush has any weight
+    fn extent(self, radius: f32) -> f32 {
+        match self {
+            Self::Gaussian => 3.0 * Self::gaussian_sigma(radius),
+            Self::Disc | Self::Square => radius + 0.5,
+        }
+    }
+
+    // the weight of the pixel at the offset from the center, the edges of the
+    // disc and square are anti-aliased over a pixel
+    fn weight(self, dx: f32, dy: f32, radius: f32) -> f32 {
+        match self {
+            Self::Gaussian => {
+                let sigma = Self::gaussian_sigma(radius);
+                GAUSSIAN_PEAK
+                    * (-(dx * dx + dy * dy) / (2.0 * sigma * sigma)).exp()
+            },
+            Self::Disc => {
+                (radius + 0.5 - (dx * dx + dy * dy).sqrt()).clamp(0.0, 1.0)
+            },
+            Self::Square => {
+                (radius + 0.5 - dx.abs()).clamp(0.0, 1.0)
+                    * (radius + 0.5 - dy.abs()).clamp(0.0, 1.0)
+            },
+        }
+    }
+}
+
+impl Default for Brush {
+    fn default() -> Self {
+        Self {
+            radius: 0.0,
+            falloff: BrushFalloff::Gaussian,
+            speed_size: 0.0,
+        }
+    }
+}
+
+impl Brush {
+    fn radius_at(&self, speed: f32) -> f32 {
+        self.radius + self.speed_size * speed
+    }
 }
 
 impl Image {
-    pub fn new(width: usize, height: usize, background: Color) -> Self {
+    pub fn new(width: usize, height: usize, background: Background) -> Self {
+        let background_pixels = background.pixels(width, height, Dither::None);
         Self {
             width,
             height,
             background,
-            pixels: vec![background; width * height],
+            blend_mode: BlendMode::Normal,
+            blend_space: BlendSpace::Srgb,
+            trail_style: TrailStyle::Dots,
+            brush: Brush::default(),
+            dither: Dither::None,
+            pixels: background_pixels.clone(),
+            background_pixels,
+            buffer: Buffer::Direct(Vec::new()),
+            coverage: Vec::new(),
         }
     }
 
@@ -26,42 +247,171 @@ impl Image {
         self.height
     }
 
+    pub fn background(&self) -> Background {
+        self.background
+    }
+
+    pub fn background_pixels(&self) -> &[Color] {
+        &self.background_pixels
+    }
+
+    // only takes effect where the image is cleared or faded, or when it is
+    // resized
+    pub fn set_background(&mut self, background: Background) {
+        if background == self.background {
+            return;
+        }
+        self.background = background;
+        self.background_pixels =
+            background.pixels(self.width, self.height, self.dither);
+    }
+
+    pub fn dither(&self) -> Dither {
+        self.dither
+    }
+
+    // like the background, only takes effect where the image is cleared or
+    // faded, or when it is resized
+    pub fn set_dither(&mut self, dither: Dither) {
+        if dither == self.dither {
+            return;
+        }
+        self.dither = dither;
+        self.background_pixels =
+            self.background.pixels(self.width, self.height, dither);
+    }
+
+    pub fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) {
+        self.blend_mode = blend_mode;
+    }
+
+    // the accumulate and density buffers always blend in linear light
+    pub fn blend_space(&self) -> BlendSpace {
+        self.blend_space
+    }
+
+    pub fn set_blend_space(&mut self, blend_space: BlendSpace) {
+        self.blend_space = blend_space;
+    }
+
+    pub fn trail_style(&self) -> TrailStyle {
+        self.trail_style
+    }
+
+    pub fn set_trail_style(&mut self, trail_style: TrailStyle) {
+        self.trail_style = trail_style;
+    }
+
+    pub fn brush(&self) -> Brush {
+        self.brush
+    }
+
+    pub fn set_brush(&mut self, brush: Brush) {
+        self.brush = brush;
+    }
+
+    // switching to accumulating keeps what has been drawn, but the density
+    // histogram always starts empty
+    pub fn set_render(&mut self, render: Render) {
+        match (render, &mut self.buffer) {
+            (Render::Direct, Buffer::Direct(_)) => {},
+            (Render::Direct, _) => self.buffer = Buffer::Direct(Vec::new()),
+            (Render::Accumulate(tone_map), Buffer::Accumulate(accumulator)) => {
+                accumulator.set_tone_map(tone_map);
+            },
+            (Render::Accumulate(tone_map), _) => {
+                self.buffer = Buffer::Accumulate(Accumulator::new(
+                    self.width,
+                    self.height,
+                    &self.pixels,
+                    tone_map,
+                ));
+            },
+            (Render::Density(density_map), Buffer::Density(histogram)) => {
+                histogram.set_density_map(density_map);
+            },
+            (Render::Density(density_map), _) => {
+                self.buffer = Buffer::Density(Histogram::new(
+                    self.width,
+                    self.height,
+                    density_map,
+                ));
+            },
+        }
+    }
+
+    pub fn tone_map(&mut self) {
+        match &self.buffer {
+            Buffer::Direct(_) => {},
+            Buffer::Accumulate(accumulator) => {
+                accumulator.tone_map(&mut self.pixels, self.dither);
+            },
+            Buffer::Density(histogram) => {
+                histogram.tone_map(
+                    &mut self.pixels,
+                    &self.background_pixels,
+                    self.dither,
+                );
+            },
+        }
+    }
+
+    pub fn pixels(&self) -> &[Color] {
+        &self.pixels
+    }
+
     pub fn put_pixel(&mut self, x: usize, y: usize, color: Color) {
         self.pixels[x + y * self.width] = color;
     }
 
     pub fn blend_pixel(&mut self, x: usize, y: usize, color: Color) {
-        let p = &mut self.pixels[x + y * self.width];
-        *p = p.blend(color);
+        let linear_color = LinearColor::from_color(color);
+        self.plot(x, y, color, linear_color, 1.0);
     }
 
-    pub fn draw_particle(&mut self, x: f32, y: f32, color: Color) {
-        macro_rules! calc {
-            ($x:expr, $w:expr) => {{
-                let w = $w;
-                let mut x = $x;
-                let wf = w as f32;
-                x -= 0.5;
-                if x <= -1.0 || x >= wf {
-                    (None, None)
-                } else if x < 0.0 {
-                    let xf = 1.0 + x;
-                    (None, Some((0, xf)))
-                } else if x >= wf - 1.0 {
-                    let xf = x.fract();
-                    (Some((w - 1, (1.0 - xf))), None)
-                } else {
-                    let xf = x.fract();
-                    let x = x as usize;
-                    (Some((x, (1.0 - xf))), Some((x + 1, xf)))
-                }
-            }};
+    // blends the color faded by the weight into the pixel, the linear color
+    // is passed in so callers plotting many pixels only convert once
+    fn plot(
+        &mut self,
+        x: usize,
+        y: usize,
+        color: Color,
+        linear_color: LinearColor,
+        weight: f32,
+    ) {
+        match &mut self.buffer {
+            Buffer::Direct(_) => {
+                let p = &mut self.pixels[x + y * self.width];
+                *p = match self.blend_space {
+                    BlendSpace::Srgb => {
+                        p.blend_mode(color.fade(weight), self.blend_mode)
+                    },
+                    BlendSpace::Linear => LinearColor::from_color(*p)
+                        .blend_mode(linear_color.fade(weight), self.blend_mode)
+                        .to_color(),
+                };
+            },
+            Buffer::Accumulate(accumulator) => {
+                // fade in floating point so faint colors don't round away
+                let c = linear_color.fade(weight);
+                accumulator.blend_pixel(x, y, c, self.blend_mode);
+            },
+            Buffer::Density(histogram) => {
+                histogram.add(x, y, linear_color, weight);
+            },
         }
+    }
+
+    pub fn draw_particle(&mut self, x: f32, y: f32, color: Color) {
+        let linear_color = LinearColor::from_color(color);
         macro_rules! write {
             ($x:expr, $y:expr) => {
                 if let (Some((x, xf)), Some((y, yf))) = ($x, $y) {
-                    let c = color.fade(xf * yf);
-                    self.blend_pixel(x, y, c);
+                    self.plot(x, y, color, linear_color, xf * yf);
                 }
             };
         }
@@ -73,6 +423,156 @@ impl Image {
         write!(x1, y1);
     }
 
+    // draws the step a particle took since the last frame in the trail style,
+    // there is no step on the first frame
+    pub fn draw_trail(
+        &mut self,
+        from: Option<(f32, f32)>,
+        (x, y): (f32, f32),
+        color: Color,
+    ) {
+        match (self.trail_style, from) {
+            (TrailStyle::Segments, Some((x0, y0))) => {
+                self.draw_segment(x0, y0, x, y, color);
+            },
+            _ => {
+                let speed = from.map_or(0.0, |(x0, y0)| {
+                    ((x - x0) * (x - x0) + (y - y0) * (y - y0)).sqrt()
+                });
+                self.draw_dot(x, y, speed, color);
+            },
+        }
+    }
+
+    // a single brush stamp, or a bilinear splat without a brush
+    fn draw_dot(&mut self, x: f32, y: f32, speed: f32, color: Color) {
+        let radius = self.brush.radius_at(speed);
+        if radius > 0.0 {
+            self.draw_brush(x, y, radius, color);
+        } else {
+            self.draw_particle(x, y, color);
+        }
+    }
+
+    pub fn draw_brush(&mut self, x: f32, y: f32, radius: f32, color: Color) {
+        let mut coverage = std::mem::take(&mut self.coverage);
+        coverage.clear();
+        self.stamp_brush(&mut coverage, x, y, radius);
+        self.blend_coverage(&mut coverage, color, f32::max);
+        self.coverage = coverage;
+    }
+
+    // a line from one point to the other, leaving out the start point so
+    // segments joined end to end only cover the joint once. without a brush
+    // it is an anti-aliased 1 pixel wide line made of bilinear splats at most
+    // a pixel apart, weighted by the length they cover. a particle that
+    // didn't move is drawn as a dot, like in the dots style.
+    pub fn draw_segment(
+        &mut self,
+        x0: f32,
+        y0: f32,
+        x1: f32,
+        y1: f32,
+        color: Color,
+    ) {
+        let dx = x1 - x0;
+        let dy = y1 - y0;
+        let length = (dx * dx + dy * dy).sqrt();
+        if length == 0.0 {
+            self.draw_dot(x1, y1, 0.0, color);
+            return;
+        }
+        let radius = self.brush.radius_at(length);
+
+        let mut coverage = std::mem::take(&mut self.coverage);
+        coverage.clear();
+        if radius > 0.0 {
+            // brush stamps close enough together to make a smooth stroke,
+            // their coverage isn't summed so the stroke is as opaque as a
+            // single stamp
+            let step_count = (length / (radius / 2.0).min(1.0)).ceil() as usize;
+            for step in 1..=step_count {
+                let t = step as f32 / step_count as f32;
+                self.stamp_brush(
+                    &mut coverage,
+                    x0 + dx * t,
+                    y0 + dy * t,
+                    radius,
+                );
+            }
+            self.blend_coverage(&mut coverage, color, f32::max);
+        } else {
+            let step_count = length.ceil() as usize;
+            let step_weight = length / step_count as f32;
+            for step in 1..=step_count {
+                let t = step as f32 / step_count as f32;
+                let (xa, xb) = calc!(x0 + dx * t, self.width);
+                let (ya, yb) = calc!(y0 + dy * t, self.height);
+                for (x, y) in [(xa, ya), (xa, yb), (xb, ya), (xb, yb)] {
+                    if let (Some((x, xf)), Some((y, yf))) = (x, y) {
+                        coverage
+                            .push((x + y * self.width, xf * yf * step_weight));
+                    }
+                }
+            }
+            self.blend_coverage(&mut coverage, color, |a, b| a + b);
+        }
+        self.coverage = coverage;
+    }
+
+    // adds the weight of the brush centered on the point for every pixel it
+    // touches, pixels off the edge of the image are skipped like in `calc!`
+    fn stamp_brush(
+        &self,
+        coverage: &mut Vec<(usize, f32)>,
+        x: f32,
+        y: f32,
+        radius: f32,
+    ) {
+        let extent = self.brush.falloff.extent(radius);
+        // pixel centers are at half coordinates
+        let x_min = (x - 0.5 - extent).ceil().max(0.0);
+        let y_min = (y - 0.5 - extent).ceil().max(0.0);
+        let x_max = (x - 0.5 + extent).floor().min(self.width as f32 - 1.0);
+        let y_max = (y - 0.5 + extent).floor().min(self.height as f32 - 1.0);
+        if x_min > x_max || y_min > y_max {
+            return;
+        }
+        for py in y_min as usize..=y_max as usize {
+            let dy = py as f32 + 0.5 - y;
+            for px in x_min as usize..=x_max as usize {
+                let dx = px as f32 + 0.5 - x;
+                let weight = self.brush.falloff.weight(dx, dy, radius);
+                if weight > 0.0 {
+                    coverage.push((px + py * self.width, weight));
+                }
+            }
+        }
+    }
+
+    // merges the coverage per pixel so each one is only blended once,
+    // blending every splat would compound the opacity where they overlap
+    fn blend_coverage(
+        &mut self,
+        coverage: &mut [(usize, f32)],
+        color: Color,
+        merge: impl Fn(f32, f32) -> f32,
+    ) {
+        coverage.sort_unstable_by_key(|(idx, _weight)| *idx);
+        let linear_color = LinearColor::from_color(color);
+        let mut splats = coverage.iter().copied().peekable();
+        while let Some((idx, mut weight)) = splats.next() {
+            while let Some((_idx, next_weight)) =
+                splats.next_if(|(next_idx, _weight)| *next_idx == idx)
+            {
+                weight = merge(weight, next_weight);
+            }
+            let x = idx % self.width;
+            let y = idx / self.width;
+            self.plot(x, y, color, linear_color, weight.min(1.0));
+        }
+    }
+
     pub fn to_image_data(&self) -> web_sys::ImageData {
         let data = self.pixels.as_bytes();
         let sw = self.width as u32;
@@ -85,98 +585,284 @@ impl Image {
         .unwrap()
     }
 
+    pub fn encode_png(&self) -> Result<Vec<u8>> {
+        let mut encoder = self.png_encoder()?;
+        encoder.step(self, self.height)?;
+        encoder.finish()
+    }
+
+    // for encoding a big image a few rows at a time
+    pub fn png_encoder(&self) -> Result<PngEncoder> {
+        let png = PngBuffer::default();
+        let mut encoder = png::Encoder::new(
+            png.clone(),
+            self.width as u32,
+            self.height as u32,
+        );
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let writer = encoder.write_header()?.into_stream_writer()?;
+        Ok(PngEncoder {
+            writer,
+            png,
+            row: 0,
+        })
+    }
+
+    // any PNG, converted to 8 bit RGBA, along with its width and height
+    pub fn decode_png(png: &[u8]) -> Result<(usize, usize, Vec<Color>)> {
+        let mut decoder = png::Decoder::new(png);
+        decoder
+            .set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info()?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer)?;
+        let bytes = &buffer[..info.buffer_size()];
+        let pixels = match info.color_type {
+            png::ColorType::Rgba => bytes
+                .chunks_exact(4)
+                .map(|p| Color {
+                    r: p[0],
+                    g: p[1],
+                    b: p[2],
+                    a: p[3],
+                })
+                .collect(),
+            png::ColorType::Rgb => bytes
+                .chunks_exact(3)
+                .map(|p| Color {
+                    r: p[0],
+                    g: p[1],
+                    b: p[2],
+                    a: 255,
+                })
+                .collect(),
+            png::ColorType::GrayscaleAlpha => bytes
+                .chunks_exact(2)
+                .map(|p| Color {
+                    r: p[0],
+                    g: p[0],
+                    b: p[0],
+                    a: p[1],
+                })
+                .collect(),
+            png::ColorType::Grayscale => bytes
+                .iter()
+                .map(|&v| Color {
+                    r: v,
+                    g: v,
+                    b: v,
+                    a: 255,
+                })
+                .collect(),
+            png::ColorType::Indexed => bail!("indexed PNG was not expanded"),
+        };
+        Ok((info.width as usize, info.height as usize, pixels))
+    }
+
+    // area-averaged downscale, for thumbnails
+    pub fn scaled(&self, width: usize, height: usize) -> Image {
+        let sx = self.width as f32 / width as f32;
+        let sy = self.height as f32 / height as f32;
+        let mut image = Image::new(width, height, self.background);
+        for y2 in 0..height {
+            let y1_start = (y2 as f32 * sy) as usize;
+            let y1_end = (((y2 + 1) as f32 * sy) as usize)
+                .clamp(y1_start + 1, self.height);
+            for x2 in 0..width {
+                let x1_start = (x2 as f32 * sx) as usize;
+                let x1_end = (((x2 + 1) as f32 * sx) as usize)
+                    .clamp(x1_start + 1, self.width);
+                let mut sum = [0.0f32; 4];
+                for y1 in y1_start..y1_end {
+                    for x1 in x1_start..x1_end {
+                        let Color { r, g, b, a } =
+                            self.pixels[x1 + y1 * self.width];
+                        sum[0] += r as f32;
+                        sum[1] += g as f32;
+                        sum[2] += b as f32;
+                        sum[3] += a as f32;
+                    }
+                }
+                let n = ((x1_end - x1_start) * (y1_end - y1_start)) as f32;
+                let [r, g, b, a] = sum.map(|sum| (sum / n).round() as u8);
+                image.put_pixel(x2, y2, Color { r, g, b, a });
+            }
+        }
+        image
+    }
+
     pub fn resize(&mut self, width: usize, height: usize) {
-        // resize the image
+        self.background_pixels =
+            self.background.pixels(width, height, self.dither);
         // preserve its contents in the center of the new image
-        use std::cmp::Ordering::*;
-        let bg = self.background;
-        let w1 = self.width;
-        let h1 = self.height;
-        let w2 = width;
-        let h2 = height;
-        match (w2.cmp(&w1), h2.cmp(&h1)) {
-            (Less, Less | Equal) => {
-                let mx = (w1 - w2) / 2;
-                let my = (h1 - h2) / 2;
-                let p1 = &self.pixels;
-                let mut p2 = vec![bg; w2 * h2];
-                for y2 in 0..h2 {
-                    let y1 = y2 - my;
-                    let i1 = mx + w1 * y1;
-                    let i2 = w2 * y2;
-                    p2[i2..i2 + w2].copy_from_slice(&p1[i1..i1 + w2]);
-                }
-                self.pixels = p2;
-                self.width = width;
-                self.height = height;
-            },
-            (Less, Greater) => {
-                let mx = (w1 - w2) / 2;
-                let my = (h2 - h1) / 2;
-                let p1 = &self.pixels;
-                let mut p2 = vec![bg; w2 * h2];
-                for y1 in 0..h1 {
-                    let y2 = y1 + my;
-                    let i1 = mx + w1 * y1;
-                    let i2 = w2 * y2;
-                    p2[i2..i2 + w2].copy_from_slice(&p1[i1..i1 + w2]);
-                }
-                self.pixels = p2;
-                self.width = width;
-                self.height = height;
-            },
-            (Equal, Less) => {
-                let my = (h1 - h2) / 2;
-                let p1 = &self.pixels;
-                let p2 = p1[w1 * my..w1 * (my + h2)].to_vec();
-                self.pixels = p2;
-                self.width = width;
-                self.height = height;
-            },
-            (Equal, Equal) => {},
-            (Equal, Greater) => {
-                let my = (h2 - h1) / 2;
-                let p1 = &self.pixels;
-                let mut p2 = vec![bg; w2 * h2];
-                p2[w2 * my..w2 * (my + h1)].copy_from_slice(p1);
-                self.pixels = p2;
-                self.width = width;
-                self.height = height;
-            },
-            (Greater, Less | Equal) => {
-                let mx = (w2 - w1) / 2;
-                let my = (h1 - h2) / 2;
-                let p1 = &self.pixels;
-                let mut p2 = vec![bg; w2 * h2];
-                for y2 in 0..h2 {
-                    let y1 = y2 + my;
-                    let i1 = w1 * y1;
-                    let i2 = mx + w2 * y2;
-                    p2[i2..i2 + w1].copy_from_slice(&p1[i1..i1 + w1]);
+        self.pixels = resize_centered(
+            &self.pixels,
+            (self.width, self.height),
+            (width, height),
+            |idx| self.background_pixels[idx],
+        );
+        match &mut self.buffer {
+            Buffer::Direct(remainders) => {
+                if !remainders.is_empty() {
+                    *remainders = resize_centered(
+                        remainders,
+                        (self.width, self.height),
+                        (width, height),
+                        |_| [0.0; 4],
+                    );
                 }
-                self.pixels = p2;
-                self.width = width;
-                self.height = height;
-            },
-            (Greater, Greater) => {
-                let mx = (w2 - w1) / 2;
-                let my = (h2 - h1) / 2;
-                let p1 = &self.pixels;
-                let mut p2 = vec![bg; w2 * h2];
-                for y1 in 0..h1 {
-                    let y2 = y1 + my;
-                    let i1 = w1 * y1;
-                    let i2 = mx + w2 * y2;
-                    p2[i2..i2 + w1].copy_from_slice(&p1[i1..i1 + w1]);
+            },
+            Buffer::Accumulate(accumulator) => {
+                accumulator.resize(width, height, &self.background_pixels);
+            },
+            Buffer::Density(histogram) => histogram.resize(width, height),
+        }
+        self.width = width;
+        self.height = height;
+    }
+
+    pub fn fade(&mut self, amount: f32) {
+        if amount <= 0.0 {
+            return;
+        }
+        match &mut self.buffer {
+            Buffer::Direct(remainders) => {
+                remainders.resize(self.pixels.len(), [0.0; 4]);
+                for (idx, ((p, &background), remainder)) in self
+                    .pixels
+                    .iter_mut()
+                    .zip(&self.background_pixels)
+                    .zip(remainders)
+                    .enumerate()
+                {
+                    let offset =
+                        self.dither.offset(idx % self.width, idx / self.width);
+                    *p = p.approach(background, amount, remainder, offset);
                 }
-                self.pixels = p2;
-                self.width = width;
-                self.height = height;
             },
+            Buffer::Accumulate(accumulator) => {
+                accumulator.fade(amount, &self.background_pixels);
+            },
+            Buffer::Density(histogram) => histogram.fade(amount),
         }
     }
 
     pub fn clear(&mut self) {
-        self.pixels.fill(self.background);
+        self.pixels.copy_from_slice(&self.background_pixels);
+        match &mut self.buffer {
+            Buffer::Direct(remainders) => remainders.clear(),
+            Buffer::Accumulate(accumulator) => {
+                accumulator.clear(&self.background_pixels);
+            },
+            Buffer::Density(histogram) => histogram.clear(),
+        }
+    }
+}
+
+impl PngEncoder {
+    // encodes up to row_count more rows of the image the encoder was made for
+    // and returns whether it's done
+    pub fn step(&mut self, image: &Image, row_count: usize) -> Result<bool> {
+        let Self {
+            writer,
+            png: _,
+            row,
+        } = self;
+        let end = row.saturating_add(row_count).min(image.height);
+        let rows = &image.pixels[*row * image.width..end * image.width];
+        writer.write_all(rows.as_bytes())?;
+        *row = end;
+        Ok(*row == image.height)
+    }
+
+    pub fn finish(self) -> Result<Vec<u8>> {
+        let Self {
+            writer,
+            png,
+            row: _,
+        } = self;
+        writer.finish()?;
+        Ok(png.0.take())
+    }
+}
+
+impl Write for PngBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+pub(crate) fn resize_centered<T: Copy>(
+    p1: &[T],
+    (w1, h1): (usize, usize),
+    (w2, h2): (usize, usize),
+    bg: impl Fn(usize) -> T,
+) -> Vec<T> {
+    use std::cmp::Ordering::*;
+    match (w2.cmp(&w1), h2.cmp(&h1)) {
+        (Less, Less | Equal) => {
+            let mx = (w1 - w2) / 2;
+            let my = (h1 - h2) / 2;
+            let mut p2 = (0..w2 * h2).map(&bg).collect::<Vec<_>>();
+            for y2 in 0..h2 {
+                let y1 = y2 + my;
+                let i1 = mx + w1 * y1;
+                let i2 = w2 * y2;
+                p2[i2..i2 + w2].copy_from_slice(&p1[i1..i1 + w2]);
+            }
+            p2
+        },
+        (Less, Greater) => {
+            let mx = (w1 - w2) / 2;
+            let my = (h2 - h1) / 2;
+            let mut p2 = (0..w2 * h2).map(&bg).collect::<Vec<_>>();
+            for y1 in 0..h1 {
+                let y2 = y1 + my;
+                let i1 = mx + w1 * y1;
+                let i2 = w2 * y2;
+                p2[i2..i2 + w2].copy_from_slice(&p1[i1..i1 + w2]);
+            }
+            p2
+        },
+        (Equal, Less) => {
+            let my = (h1 - h2) / 2;
+            p1[w1 * my..w1 * (my + h2)].to_vec()
+        },
+        (Equal, Equal) => p1.to_vec(),
+        (Equal, Greater) => {
+            let my = (h2 - h1) / 2;
+            let mut p2 = (0..w2 * h2).map(&bg).collect::<Vec<_>>();
+            p2[w2 * my..w2 * (my + h1)].copy_from_slice(p1);
+            p2
+        },
+        (Greater, Less | Equal) => {
+            let mx = (w2 - w1) / 2;
+            let my = (h1 - h2) / 2;
+            let mut p2 = (0..w2 * h2).map(&bg).collect::<Vec<_>>();
+            for y2 in 0..h2 {
+                let y1 = y2 + my;
+                let i1 = w1 * y1;
+                let i2 = mx + w2 * y2;
+                p2[i2..i2 + w1].copy_from_slice(&p1[i1..i1 + w1]);
+            }
+            p2
+        },
+        (Greater, Greater) => {
+            let mx = (w2 - w1) / 2;
+            let my = (h2 - h1) / 2;
+            let mut p2 = (0..w2 * h2).map(&bg).collect::<Vec<_>>();
+            for y1 in 0..h1 {
+                let y2 = y1 + my;
+                let i1 = w1 * y1;
+                let i2 = mx + w2 * y2;
+                p2[i2..i2 + w1].copy_from_slice(&p1[i1..i1 + w1]);
+            }
+            p2
+        },
     }
 }
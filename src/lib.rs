@@ -1,27 +1,64 @@
-mod color;
+pub mod accumulator;
+pub mod background;
+pub mod camera;
+pub mod color;
+pub mod config;
+pub mod density;
+pub mod dither;
+pub mod explorer;
+pub mod export;
+pub mod graph;
 mod hooks;
-mod image;
-mod math;
+pub mod image;
+pub mod math;
+pub mod palette;
 mod renderer;
-mod world;
+pub mod world;
 
 use crate::{
-    color::Color,
+    accumulator::ToneMapping,
+    background::{Background, BackgroundStyle},
+    camera::{Camera, CameraMode},
+    color::{BlendMode, BlendSpace, Color, ColorSpace},
+    config::{
+        decode_config_str,
+        encode_config_str,
+        Config,
+        CONFIG_VERSION,
+        DEFAULT_FRAME_LIMIT,
+    },
+    dither::Dither,
+    explorer::{ExploreParams, SeedExplorer, SeedScore},
+    export::{Export, ExportParams, ExportSource},
+    graph::{parse_partners, GraphAnalysis, MAX_PARTICLE_COUNT},
     hooks::{use_element, use_element_size},
-    image::Image,
-    math::lerp,
+    image::{BrushFalloff, Image, RenderMode, TrailStyle},
+    math::{lerp, spread_range_within},
+    palette::{ColorStop, NamedPalette, Palette, PaletteMode},
     renderer::WorldRenderer,
-    world::{DisplayParams, Seed, SimParams, World},
+    world::{
+        ColorDistribution,
+        ColorMode,
+        DisplayParams,
+        Seed,
+        SimChange,
+        SimEvent,
+        SimParams,
+        World,
+        MAX_PARTICLE_COLOR_ALPHA_MID,
+        MAX_PARTICLE_COLOR_VALUE_MID,
+        MIN_PARTICLE_COLOR_ALPHA_MID,
+        MIN_PARTICLE_COLOR_VALUE_MID,
+    },
 };
-use anyhow::Result;
+use anyhow::{Context, Result};
 use base64::prelude::*;
 use dioxus::{
     logger::tracing::{debug, info, warn},
     prelude::*,
 };
 use rand::prelude::*;
-use serde::{Deserialize, Serialize};
-use std::collections::VecDeque;
+use std::{collections::VecDeque, ops::RangeInclusive};
 use wasm_bindgen::prelude::*;
 
 static HELP: &[&str] = &[
@@ -30,11 +67,87 @@ static HELP: &[&str] = &[
      partners. The particles are given random starting positions and colors. \
      All randomness uses the given seed, so all simulations with the same \
      seed, particle count, and acceleration limit will be identical. The \
-     range of randomly chosen colors for each particle can also be adjusted, \
-     but will not affect the simulation.",
+     speed limit caps how far particles move each frame, also as a power of \
+     2. With change live on, both limits can be changed while the simulation \
+     keeps running, and each change is saved in the configuration with the \
+     frame it was made at, so the run can still be replayed exactly. The \
+     partners can also be imported from a CSV file of `i,j,k` rows, meaning \
+     particle i follows j and k, or from the edges of a Graphviz DOT file. \
+     Imported partners are saved in the configuration. The range of randomly \
+     chosen colors for each particle can also be adjusted, but will not \
+     affect the simulation. Changing them, or any other display setting, \
+     keeps the simulation running and colors the trails from then on with the \
+     new settings. With redraw trails on, the existing trails are also \
+     redrawn once the settings stop changing, as long as the whole run is \
+     still in memory. The ranges can be given in the OKLCH color space as \
+     lightness, chroma, and hue, which spreads colors more evenly to the eye, \
+     with colors outside of sRGB pulled back to the nearest one of the same \
+     lightness and hue. Colors can also be picked from a gradient with any \
+     number of color stops, from named palettes like viridis or magma, or \
+     from a list of swatches. Swatches are weighted by how much of the \
+     palette each one gets, and uploading a PNG replaces them with its main \
+     colors weighted by how much of the image they cover. Instead of random \
+     colors, particles can be colored every frame by their speed, heading, \
+     acceleration, distance from the center of all the particles, or by how \
+     long the simulation has run, mapped across the hue and saturation \
+     ranges. Random colors can also be spread evenly over the ranges, around \
+     the starting ring like a color wheel, or with one color for each \
+     strongly connected group of the partner graph, and stay the same for the \
+     seed. Each particle's brightness and opacity can vary over a range as \
+     well, in every color mode, though brightness only applies to the ranges. \
+     The color cycle rotates the hue by that many degrees every frame, or \
+     moves back and forth along the other palettes with 360° for a trip there \
+     and back, which bands long trails like a rainbow. The cycle phase \
+     spreads that many degrees of offset across the particles, and saved SVG \
+     files split each path where its color changes to match.",
     "The simulation will run until the given frame limit is reached. The \
      canvas can be saved as a PNG file at any time, and the paths of all \
-     particles can be saved as an SVG.",
+     particles can be saved as an SVG. The graph of partners can be inspected \
+     and saved as a Graphviz DOT or GraphML file.",
+    "The background can be a solid color, a linear or radial gradient between \
+     two colors, or transparent, and is used in saved PNG and SVG files too. \
+     Trails are kept forever by default, so long runs eventually fill the \
+     canvas. Setting a trail fade fades the whole canvas toward the \
+     background by that percentage every frame, so only recent motion stays \
+     visible. Particles are normally painted over each other, but can also be \
+     blended with the other blend modes, like add or screen to make dense \
+     areas glow. Blending in linear light mixes colors the way light does, so \
+     overlapping complementary colors don't darken, while blending the sRGB \
+     values directly matches older versions. Trails are drawn as a dot at \
+     each particle's position every frame, which leaves gaps behind fast \
+     particles, or as line segments joining their positions. The brush radius \
+     paints bigger dots or thicker lines with a gaussian, disc, or square \
+     shape, and the brush speed size grows the radius by that much for each \
+     pixel per frame a particle is moving. The accumulate render mode keeps \
+     the canvas in high precision linear light so very faint trails still \
+     build up, and maps it to the screen with the chosen tone mapping, \
+     exposure (in stops), and gamma. The density render mode counts how often \
+     each pixel is visited, weighting each visit by the particle's opacity, \
+     and shows the log of that count in the average color of the visits, \
+     which brings out detail in runs with many particles. Vibrancy controls \
+     whether the gamma brightens the colors evenly or each color channel \
+     separately. Dithering hides the banding in dark gradients of those \
+     modes, of trails fading in the direct mode, and of the background by \
+     rounding each pixel up or down in an ordered Bayer or blue noise \
+     pattern, on screen and in saved PNG files.",
+    "Scroll on the canvas to zoom in and out around the pointer, and drag it \
+     to move around. The camera can also be rotated. In fit mode the camera \
+     zooms and moves to keep all of the particles in view, and in follow mode \
+     it keeps the center of the particles in the middle of the canvas. Trails \
+     are drawn again from the recent history whenever the camera moves. Once \
+     a run is longer than that history, the trails already drawn are kept \
+     where they are and only new trails follow the camera.",
+    "The export button renders a PNG at a bigger size than the screen, such \
+     as 8K or a print size, showing the same view with everything scaled up \
+     to match. It can render the recent history kept for the camera, which is \
+     quick, or simulate the whole run again up to the current frame. Exports \
+     too big to fit in the browser's memory fail with an error, and the limit \
+     is lower when trails fade or in the accumulate and density modes.",
+    "The seed explorer simulates many random seeds in the background with the \
+     current parameters and ranks them by how much of the canvas they cover, \
+     the variety of their trail brightness, and how far they spread out. \
+     Click a result to use its seed. The same search can be run from the \
+     command line with `followers-cli explore`.",
     "All of the configuration is saved in the URL, so you can save or share a \
      configuration just by copying the URL, and use your browser's history to \
      navigate between configurations as you change the parameters.",
@@ -63,7 +176,6 @@ fn start() -> Result<(), JsValue> {
 }
 
 const MIN_PARTICLE_COUNT: usize = 3;
-const MAX_PARTICLE_COUNT: usize = 1000000;
 const MIN_PARTICLE_COLOR_HUE_MID: f32 = 0.0;
 const MAX_PARTICLE_COLOR_HUE_MID: f32 = 360.0;
 const MIN_PARTICLE_COLOR_HUE_SPREAD: f32 = 0.0;
@@ -72,67 +184,157 @@ const MIN_PARTICLE_COLOR_SATURATION_MID: f32 = 0.0;
 const MAX_PARTICLE_COLOR_SATURATION_MID: f32 = 100.0;
 const MIN_PARTICLE_COLOR_SATURATION_SPREAD: f32 = 0.0;
 const MAX_PARTICLE_COLOR_SATURATION_SPREAD: f32 = 100.0;
-const MIN_PARTICLE_COLOR_VALUE: f32 = 1.0;
-const MAX_PARTICLE_COLOR_VALUE: f32 = 100.0;
-const MIN_PARTICLE_COLOR_ALPHA: f32 = 1.0;
-const MAX_PARTICLE_COLOR_ALPHA: f32 = 100.0;
+const MIN_PARTICLE_COLOR_VALUE_SPREAD: f32 = 0.0;
+const MAX_PARTICLE_COLOR_VALUE_SPREAD: f32 = 100.0;
+const MIN_PARTICLE_COLOR_ALPHA_SPREAD: f32 = 0.0;
+const MAX_PARTICLE_COLOR_ALPHA_SPREAD: f32 = 100.0;
+const MIN_SWATCH_WEIGHT: f32 = 0.0;
+const MAX_SWATCH_WEIGHT: f32 = 100.0;
+const EXTRACTED_SWATCH_COUNT: usize = 6;
+const MIN_COLOR_CYCLE_SPEED: f32 = -360.0;
+const MAX_COLOR_CYCLE_SPEED: f32 = 360.0;
+const MIN_COLOR_CYCLE_PHASE: f32 = 0.0;
+const MAX_COLOR_CYCLE_PHASE: f32 = 3600.0;
+const MIN_BACKGROUND_ANGLE: f32 = 0.0;
+const MAX_BACKGROUND_ANGLE: f32 = 360.0;
+const MIN_TRAIL_FADE: f32 = 0.0;
+const MAX_TRAIL_FADE: f32 = 100.0;
+const MIN_BRUSH_RADIUS: f32 = 0.0;
+const MAX_BRUSH_RADIUS: f32 = 50.0;
+const MIN_BRUSH_SPEED_SIZE: f32 = 0.0;
+const MAX_BRUSH_SPEED_SIZE: f32 = 50.0;
+const MIN_EXPOSURE: f32 = -10.0;
+const MAX_EXPOSURE: f32 = 10.0;
+const MIN_GAMMA: f32 = 0.1;
+const MAX_GAMMA: f32 = 10.0;
+const MIN_VIBRANCY: f32 = 0.0;
+const MAX_VIBRANCY: f32 = 100.0;
+const MIN_CAMERA_ZOOM: f32 = 0.01;
+const MAX_CAMERA_ZOOM: f32 = 100.0;
+const MIN_CAMERA_ROTATION: f32 = -360.0;
+const MAX_CAMERA_ROTATION: f32 = 360.0;
+// pixels of scrolling that zoom in or out by a factor of 2
+const CAMERA_WHEEL_ZOOM_SCALE: f64 = 500.0;
 const MIN_ACC_LIMIT: i32 = -10;
 const MAX_ACC_LIMIT: i32 = 10;
+const MIN_SPEED_LIMIT: i32 = -10;
+const MAX_SPEED_LIMIT: i32 = 4;
+
+const GRAPH_LEADERS_SHOWN: usize = 20;
+
+const DEFAULT_EXPLORER_SEED_COUNT: usize = 24;
+const MIN_EXPLORER_SEED_COUNT: usize = 1;
+const MAX_EXPLORER_SEED_COUNT: usize = 1000;
+const EXPLORER_THUMBNAIL_WIDTH: usize = 120;
+const EXPLORER_FRAMES_PER_TICK: usize = 50;
+
+const DEFAULT_EXPORT_WIDTH: usize = 7680;
+const DEFAULT_EXPORT_HEIGHT: usize = 4320;
+const MIN_EXPORT_SIZE: usize = 1;
+const MAX_EXPORT_SIZE: usize = 16384;
+// how many pixels to render or encode before letting the page update
+const EXPORT_PIXELS_PER_TICK: usize = 1 << 22;
+static EXPORT_PRESETS: &[(&str, usize, usize)] = &[
+    ("1080p", 1920, 1080),
+    ("4K", 3840, 2160),
+    ("8K", 7680, 4320),
+    ("A4 300 dpi", 3508, 2480),
+    ("A3 300 dpi", 4961, 3508),
+    ("A2 300 dpi", 7016, 4961),
+];
 
 const PALETTE_WIDTH: usize = 100;
 const PALETTE_HEIGHT: usize = 40;
 
-const BACKGROUND_COLOR: Color = Color::hex(0x000000ff);
+const MAX_FILE_NAME_CONFIG_LEN: usize = 200;
 
 const CONFIG_COMMIT_DELAY_MS: u32 = 400;
+const REDRAW_TRAILS_DELAY_MS: u32 = 200;
 const CONFIG_QUERY_PARAM: &str = "c";
 
-#[derive(Debug, Serialize, Deserialize)]
-struct Config {
-    version: u64,
-    sim_params: SimParams,
-    display_params: DisplayParams,
-    frame_limit: usize,
-}
-
 #[component]
 fn App() -> Element {
     let mut seed_rng = use_signal(thread_rng);
-    let mut sim_params = use_signal(|| SimParams {
-        seed: Seed::from_hash(0x27e3771584a46455),
-        particle_count: 1000,
-        acc_limit: -1,
-    });
-    let mut display_params = use_signal(|| DisplayParams {
-        particle_color_hue_mid: 120.0,
-        particle_color_hue_spread: 240.0,
-        particle_color_saturation_mid: 70.0,
-        particle_color_saturation_spread: 20.0,
-        particle_color_value: 100.0,
-        particle_color_alpha: 6.0,
-    });
-    let mut frame_limit = use_signal(|| 60 * 60);
+    let mut sim_params = use_signal(SimParams::default);
+    let mut display_params = use_signal(DisplayParams::default);
+    let mut frame_limit = use_signal(|| DEFAULT_FRAME_LIMIT);
+    let mut camera = use_signal(Camera::default);
+    // what's actually shown, which fit and follow move on their own without
+    // changing the saved camera
+    let view_camera = use_signal(Camera::default);
+    let mut camera_drag = use_signal(|| None::<(f64, f64)>);
     let mut world = use_signal(|| {
         World::new(&sim_params.peek(), &display_params.peek()).unwrap()
     });
     let mut world_renderer = use_signal(|| None::<WorldRenderer>);
+    let mut redraw_trails = use_signal(|| false);
+    let mut live_changes = use_signal(|| false);
     let mut palette_image = use_signal(|| {
-        Image::new(PALETTE_WIDTH, PALETTE_HEIGHT, Color::transparent())
+        Image::new(
+            PALETTE_WIDTH,
+            PALETTE_HEIGHT,
+            Background::solid(Color::transparent()),
+        )
     });
+    let mut partners_error = use_signal(|| None::<String>);
+    let mut palette_error = use_signal(|| None::<String>);
     let mut show_help = use_signal(|| false);
+    let mut show_graph = use_signal(|| false);
+    let mut graph_analysis = use_signal(|| None::<GraphAnalysis>);
+    let mut show_explorer = use_signal(|| false);
+    let mut explorer_seed_count = use_signal(|| DEFAULT_EXPLORER_SEED_COUNT);
+    let mut explorer_running = use_signal(|| false);
+    let mut explorer_generation = use_signal(|| 0usize);
+    let mut explorer_results = use_signal(Vec::<ExplorerResult>::new);
+    let mut show_export = use_signal(|| false);
+    let mut export_width = use_signal(|| DEFAULT_EXPORT_WIDTH);
+    let mut export_height = use_signal(|| DEFAULT_EXPORT_HEIGHT);
+    let mut export_source = use_signal(ExportSource::default);
+    let mut exporting = use_signal(|| false);
+    let mut export_error = use_signal(|| None::<String>);
+
+    let restart = use_callback(move |()| {
+        let new_world =
+            match World::new(&sim_params.peek(), &display_params.peek()) {
+                Ok(world) => world,
+                Err(error) => {
+                    warn!("failed to create world: {:?}", error);
+                    return;
+                },
+            };
+        world.set(new_world);
+        graph_analysis.set(
+            show_graph
+                .peek()
+                .then(|| world.peek().partner_graph().analyze()),
+        );
+        if let Some(world_renderer) = &mut *world_renderer.write() {
+            world_renderer.clear();
+            world_renderer.resume();
+        }
+    });
 
     let config_str = move || {
         encode_config_str(Config {
-            version: 1,
+            version: CONFIG_VERSION,
             sim_params: sim_params.read().clone(),
             display_params: display_params.read().clone(),
             // TODO: store current frame, not frame limit
             frame_limit: *frame_limit.read(),
+            camera: camera.read().clone(),
         })
     };
 
-    let file_name =
-        move || format!("{}-{}", sim_params.read().file_name(), config_str());
+    let file_name = move || {
+        let file_name = sim_params.read().file_name();
+        let config_str = config_str();
+        // imported partners make the config too long to fit in a file name
+        if config_str.len() > MAX_FILE_NAME_CONFIG_LEN {
+            file_name
+        } else {
+            format!("{file_name}-{config_str}")
+        }
+    };
 
     let (world_canvas_element, on_world_canvas_mounted) =
         use_element::<web_sys::HtmlCanvasElement>();
@@ -170,8 +372,78 @@ fn App() -> Element {
         } else {
             return;
         };
-        sim_params.write().acc_limit =
-            acc_limit.clamp(MIN_ACC_LIMIT, MAX_ACC_LIMIT);
+        let acc_limit = acc_limit.clamp(MIN_ACC_LIMIT, MAX_ACC_LIMIT);
+        if *live_changes.peek() {
+            sim_params.write().push_event(SimEvent {
+                frame: world.peek().age(),
+                change: SimChange::AccLimit(acc_limit),
+            });
+        } else {
+            sim_params.write().acc_limit = acc_limit;
+        }
+    });
+
+    let on_input_speed_limit = use_callback(move |event: Event<FormData>| {
+        let speed_limit = if let Ok(speed_limit) = event.parsed::<i32>() {
+            speed_limit
+        } else {
+            return;
+        };
+        let speed_limit = speed_limit.clamp(MIN_SPEED_LIMIT, MAX_SPEED_LIMIT);
+        if *live_changes.peek() {
+            sim_params.write().push_event(SimEvent {
+                frame: world.peek().age(),
+                change: SimChange::SpeedLimit(speed_limit),
+            });
+        } else {
+            sim_params.write().speed_limit = speed_limit;
+        }
+    });
+
+    let on_input_live_changes = use_callback(move |event: Event<FormData>| {
+        live_changes.set(event.checked());
+    });
+
+    let on_click_clear_events = use_callback(move |_: Event<MouseData>| {
+        sim_params.write().events.clear();
+    });
+
+    let on_input_partners_file = use_callback(move |event: Event<FormData>| {
+        let files = if let Some(files) = event.files() {
+            files
+        } else {
+            return;
+        };
+        let file_name =
+            if let Some(file_name) = files.files().into_iter().next() {
+                file_name
+            } else {
+                return;
+            };
+        spawn(async move {
+            let partners = match files
+                .read_file_to_string(&file_name)
+                .await
+                .with_context(|| format!("failed to read {file_name}"))
+                .and_then(|s| parse_partners(&s))
+            {
+                Ok(partners) => partners,
+                Err(error) => {
+                    warn!("failed to import partners: {:?}", error);
+                    partners_error.set(Some(format!("{error:#}")));
+                    return;
+                },
+            };
+            partners_error.set(None);
+            let sim_params = &mut *sim_params.write();
+            sim_params.particle_count = partners.len();
+            sim_params.partners = Some(partners);
+        });
+    });
+
+    let on_click_clear_partners = use_callback(move |_: Event<MouseData>| {
+        partners_error.set(None);
+        sim_params.write().partners = None;
     });
 
     let on_input_particle_color_hue_mid =
@@ -231,7 +503,7 @@ fn App() -> Element {
                 );
         });
 
-    let on_input_particle_color_value =
+    let on_input_particle_color_value_mid =
         use_callback(move |event: Event<FormData>| {
             let particle_value =
                 if let Ok(particle_value) = event.parsed::<f32>() {
@@ -239,11 +511,29 @@ fn App() -> Element {
                 } else {
                     return;
                 };
-            display_params.write().particle_color_value = particle_value
-                .clamp(MIN_PARTICLE_COLOR_VALUE, MAX_PARTICLE_COLOR_VALUE);
+            display_params.write().particle_color_value_mid = particle_value
+                .clamp(
+                    MIN_PARTICLE_COLOR_VALUE_MID,
+                    MAX_PARTICLE_COLOR_VALUE_MID,
+                );
+        });
+
+    let on_input_particle_color_value_spread =
+        use_callback(move |event: Event<FormData>| {
+            let particle_value_spread =
+                if let Ok(particle_value_spread) = event.parsed::<f32>() {
+                    particle_value_spread
+                } else {
+                    return;
+                };
+            display_params.write().particle_color_value_spread =
+                particle_value_spread.clamp(
+                    MIN_PARTICLE_COLOR_VALUE_SPREAD,
+                    MAX_PARTICLE_COLOR_VALUE_SPREAD,
+                );
         });
 
-    let on_input_particle_color_alpha =
+    let on_input_particle_color_alpha_mid =
         use_callback(move |event: Event<FormData>| {
             let particle_alpha =
                 if let Ok(particle_alpha) = event.parsed::<f32>() {
@@ -251,481 +541,2194 @@ fn App() -> Element {
                 } else {
                     return;
                 };
-            display_params.write().particle_color_alpha = particle_alpha
-                .clamp(MIN_PARTICLE_COLOR_ALPHA, MAX_PARTICLE_COLOR_ALPHA);
+            display_params.write().particle_color_alpha_mid = particle_alpha
+                .clamp(
+                    MIN_PARTICLE_COLOR_ALPHA_MID,
+                    MAX_PARTICLE_COLOR_ALPHA_MID,
+                );
         });
 
-    let on_input_frame_limit = use_callback(move |event: Event<FormData>| {
-        let frame_limit_ = if let Ok(frame_limit) = event.parsed::<usize>() {
-            frame_limit
+    let on_input_particle_color_alpha_spread =
+        use_callback(move |event: Event<FormData>| {
+            let particle_alpha_spread =
+                if let Ok(particle_alpha_spread) = event.parsed::<f32>() {
+                    particle_alpha_spread
+                } else {
+                    return;
+                };
+            display_params.write().particle_color_alpha_spread =
+                particle_alpha_spread.clamp(
+                    MIN_PARTICLE_COLOR_ALPHA_SPREAD,
+                    MAX_PARTICLE_COLOR_ALPHA_SPREAD,
+                );
+        });
+
+    let on_input_palette_mode = use_callback(move |event: Event<FormData>| {
+        let mode = if let Some(mode) = PaletteMode::from_name(&event.value()) {
+            mode
         } else {
             return;
         };
-        frame_limit.set(frame_limit_.max(1));
-        if let Some(world_renderer) = &mut *world_renderer.write() {
-            world_renderer.resume();
-        }
+        display_params.write().palette.mode = mode;
     });
 
-    let on_click_pause_resume = use_callback(move |_: Event<MouseData>| {
-        if let Some(world_renderer) = &mut *world_renderer.write() {
-            world_renderer.pause_resume();
-        }
+    let on_input_color_space = use_callback(move |event: Event<FormData>| {
+        let space = if let Some(space) = ColorSpace::from_name(&event.value()) {
+            space
+        } else {
+            return;
+        };
+        display_params.write().color_space = space;
     });
 
-    let on_click_reset = use_callback(move |_: Event<MouseData>| {
-        sim_params.write();
+    let on_input_named_palette = use_callback(move |event: Event<FormData>| {
+        let named = if let Some(named) = NamedPalette::from_name(&event.value())
+        {
+            named
+        } else {
+            return;
+        };
+        display_params.write().palette.named = named;
     });
 
-    let on_click_save = use_callback(move |_: Event<MouseData>| {
-        let world_canvas_element = &*world_canvas_element.read();
-        let world_canvas_element =
-            if let Some(world_canvas_element) = world_canvas_element {
-                world_canvas_element
+    let on_input_gradient_stop_color =
+        use_callback(move |(idx, event): (usize, Event<FormData>)| {
+            let color = if let Some(color) = Color::parse_hex(&event.value()) {
+                color
             } else {
                 return;
             };
-        let file_name = format!("{}.png", file_name());
-        let document = world_canvas_element.owner_document().unwrap();
-        let closure = Closure::<dyn FnMut(Option<web_sys::Blob>)>::new(
-            move |blob: Option<web_sys::Blob>| {
-                let blob = if let Some(blob) = blob {
-                    blob
-                } else {
-                    return;
-                };
-                download_blob(&document, &blob, &file_name);
-            },
-        );
-        world_canvas_element
-            .to_blob(closure.as_ref().unchecked_ref())
-            .unwrap();
-        closure.forget(); // FIXME: don't leak
-    });
+            if let Some(stop) =
+                display_params.write().palette.stops.get_mut(idx)
+            {
+                stop.color = color;
+            }
+        });
 
-    let on_click_save_svg = use_callback(move |_: Event<MouseData>| {
-        let file_name = format!("{}.svg", file_name());
-        let window = web_sys::window().unwrap();
-        let document = window.document().unwrap();
-        defer(&window, move || {
-            let svg = world.peek().generate_svg(BACKGROUND_COLOR);
-            // TODO: handle errors?
-            let blob = web_sys::Blob::new_with_str_sequence(&vec![svg].into())
-                .unwrap();
-            download_blob(&document, &blob, &file_name);
+    let on_input_gradient_stop_position =
+        use_callback(move |(idx, event): (usize, Event<FormData>)| {
+            let position = if let Ok(position) = event.parsed::<f32>() {
+                position
+            } else {
+                return;
+            };
+            if let Some(stop) =
+                display_params.write().palette.stops.get_mut(idx)
+            {
+                stop.position = position.clamp(0.0, 100.0) / 100.0;
+            }
         });
-    });
 
-    let on_click_help = use_callback(move |_: Event<MouseData>| {
-        show_help.with_mut(|show_help| {
-            *show_help = !*show_help;
+    let on_click_remove_gradient_stop = use_callback(move |idx: usize| {
+        display_params.with_mut(|display_params| {
+            let stops = &mut display_params.palette.stops;
+            if stops.len() > 1 && idx < stops.len() {
+                stops.remove(idx);
+            }
         });
     });
 
-    use_effect(move || {
-        let new_world =
-            match World::new(&sim_params.read(), &display_params.read()) {
-                Ok(world) => world,
-                Err(error) => {
-                    warn!("failed to create world: {:?}", error);
-                    return;
-                },
+    let on_click_add_gradient_stop =
+        use_callback(move |_: Event<MouseData>| {
+            display_params.with_mut(|display_params| {
+                let palette = &mut display_params.palette;
+                // split the widest gap between stops
+                let mut positions = palette
+                    .stops
+                    .iter()
+                    .map(|stop| stop.position)
+                    .collect::<Vec<_>>();
+                positions.sort_by(f32::total_cmp);
+                let position = positions
+                    .windows(2)
+                    .max_by(|a, b| (a[1] - a[0]).total_cmp(&(b[1] - b[0])))
+                    .map_or(0.5, |pair| (pair[0] + pair[1]) / 2.0);
+                let color =
+                    palette.sample(position).unwrap_or(Color::hex(0xffffffff));
+                palette.stops.push(ColorStop { position, color });
+            });
+        });
+
+    let on_input_swatch =
+        use_callback(move |(idx, event): (usize, Event<FormData>)| {
+            let color = if let Some(color) = Color::parse_hex(&event.value()) {
+                color
+            } else {
+                return;
             };
-        world.set(new_world);
-        if let Some(world_renderer) = &mut *world_renderer.write() {
-            world_renderer.clear();
-            world_renderer.resume();
-        }
+            if let Some(swatch) =
+                display_params.write().palette.swatches.get_mut(idx)
+            {
+                *swatch = color;
+            }
+        });
+
+    let on_click_remove_swatch = use_callback(move |idx: usize| {
+        display_params.with_mut(|display_params| {
+            let palette = &mut display_params.palette;
+            if palette.swatches.len() > 1 && idx < palette.swatches.len() {
+                palette.swatches.remove(idx);
+                if idx < palette.swatch_weights.len() {
+                    palette.swatch_weights.remove(idx);
+                }
+            }
+        });
     });
 
-    use_effect(move || {
-        let world_canvas_element = &*world_canvas_element.read();
-        let world_canvas_element =
-            if let Some(world_canvas_element) = world_canvas_element {
-                world_canvas_element
+    let on_input_swatch_weight =
+        use_callback(move |(idx, event): (usize, Event<FormData>)| {
+            let weight = if let Ok(weight) = event.parsed::<f32>() {
+                weight
             } else {
                 return;
             };
-        let world_canvas_size = *world_canvas_size.read();
-        let world_canvas_size =
-            if let Some(world_canvas_size) = world_canvas_size {
-                world_canvas_size
+            display_params.with_mut(|display_params| {
+                let palette = &mut display_params.palette;
+                if idx >= palette.swatches.len() {
+                    return;
+                }
+                if palette.swatch_weights.len() <= idx {
+                    palette.swatch_weights.resize(idx + 1, 1.0);
+                }
+                palette.swatch_weights[idx] =
+                    weight.clamp(MIN_SWATCH_WEIGHT, MAX_SWATCH_WEIGHT);
+            });
+        });
+
+    let on_input_palette_image = use_callback(move |event: Event<FormData>| {
+        let files = if let Some(files) = event.files() {
+            files
+        } else {
+            return;
+        };
+        let file_name =
+            if let Some(file_name) = files.files().into_iter().next() {
+                file_name
             } else {
                 return;
             };
-        world_canvas_element.set_width(world_canvas_size.width as u32);
-        world_canvas_element.set_height(world_canvas_size.height as u32);
-        world_renderer.with_mut(|renderer| {
-            if let Some(renderer) = renderer {
-                renderer.update(world_canvas_element);
-            } else {
-                *renderer = Some(WorldRenderer::new(
-                    world_canvas_element,
-                    world,
-                    BACKGROUND_COLOR,
-                    frame_limit,
-                ));
-            }
+        spawn(async move {
+            let extracted = match files
+                .read_file(&file_name)
+                .await
+                .with_context(|| format!("failed to read {file_name}"))
+                .and_then(|png| Palette::from_png(&png, EXTRACTED_SWATCH_COUNT))
+            {
+                Ok(extracted) => extracted,
+                Err(error) => {
+                    warn!("failed to extract palette: {:?}", error);
+                    palette_error.set(Some(format!("{error:#}")));
+                    return;
+                },
+            };
+            palette_error.set(None);
+            let palette = &mut display_params.write().palette;
+            palette.mode = PaletteMode::Swatches;
+            palette.swatches = extracted.swatches;
+            palette.swatch_weights = extracted.swatch_weights;
         });
     });
 
-    use_effect(move || {
-        let DisplayParams {
-            particle_color_hue_mid,
-            particle_color_hue_spread,
-            particle_color_saturation_mid,
-            particle_color_saturation_spread,
-            particle_color_value,
-            particle_color_alpha: _,
-        } = &*display_params.read();
-        let palette_image = &mut *palette_image.write();
-        for y in 0..PALETTE_HEIGHT {
-            for x in 0..PALETTE_WIDTH {
-                let color = Color::hsva(
-                    lerp(
-                        x as f32,
-                        0.0,
-                        (PALETTE_WIDTH - 1) as f32,
-                        particle_color_hue_mid
-                            - particle_color_hue_spread / 2.0,
-                        particle_color_hue_mid
-                            + particle_color_hue_spread / 2.0,
-                    ),
-                    lerp(
-                        y as f32,
-                        (PALETTE_HEIGHT - 1) as f32,
-                        0.0,
-                        particle_color_saturation_mid
-                            - particle_color_saturation_spread / 2.0,
-                        particle_color_saturation_mid
-                            + particle_color_saturation_spread / 2.0,
-                    ),
-                    *particle_color_value,
-                    100.0,
-                );
-                palette_image.put_pixel(x, y, color);
-            }
-        }
-    });
-
-    use_effect(move || {
-        let palette_canvas_element = &*palette_canvas_element.read();
-        if let Some(palette_canvas_element) = palette_canvas_element {
-            let context = palette_canvas_element
-                .get_context("2d")
-                .unwrap()
-                .unwrap()
-                .dyn_into::<web_sys::CanvasRenderingContext2d>()
-                .unwrap();
-            let image_data = palette_image.read().to_image_data();
-            context.put_image_data(&image_data, 0.0, 0.0).unwrap();
-        }
+    let on_click_add_swatch = use_callback(move |_: Event<MouseData>| {
+        display_params.with_mut(|display_params| {
+            let swatches = &mut display_params.palette.swatches;
+            let color =
+                swatches.last().copied().unwrap_or(Color::hex(0xffffffff));
+            swatches.push(color);
+        });
     });
 
-    // read config from URL
-    use_effect(move || {
-        let mut read_config = move || {
-            let window = web_sys::window().unwrap();
-            let url =
-                web_sys::Url::new(&window.location().href().unwrap()).unwrap();
-            let config = if let Some(config) = url
-                .search_params()
-                .get(CONFIG_QUERY_PARAM)
-                .as_deref()
-                .and_then(decode_config_str)
-            {
-                config
+    let on_input_color_mode = use_callback(move |event: Event<FormData>| {
+        let color_mode =
+            if let Some(color_mode) = ColorMode::from_name(&event.value()) {
+                color_mode
             } else {
                 return;
             };
-            debug!("config from URL: {:#?}", config);
-            let Config {
-                version,
-                sim_params: sim_params_,
-                display_params: display_params_,
-                frame_limit: frame_limit_,
-            } = config;
-            if version != 1 {
+        display_params.write().color_mode = color_mode;
+    });
+
+    let on_input_redraw_trails = use_callback(move |event: Event<FormData>| {
+        redraw_trails.set(event.checked());
+    });
+
+    let on_input_dither = use_callback(move |event: Event<FormData>| {
+        let dither = if let Some(dither) = Dither::from_name(&event.value()) {
+            dither
+        } else {
+            return;
+        };
+        display_params.write().dither = dither;
+    });
+
+    let on_input_color_distribution =
+        use_callback(move |event: Event<FormData>| {
+            let distribution = if let Some(distribution) =
+                ColorDistribution::from_name(&event.value())
+            {
+                distribution
+            } else {
                 return;
-            }
-            sim_params.set(sim_params_);
-            display_params.set(display_params_);
-            frame_limit.set(frame_limit_);
+            };
+            display_params.write().color_distribution = distribution;
+        });
+
+    let on_input_color_cycle_speed =
+        use_callback(move |event: Event<FormData>| {
+            let speed = if let Ok(speed) = event.parsed::<f32>() {
+                speed
+            } else {
+                return;
+            };
+            display_params.write().color_cycle_speed =
+                speed.clamp(MIN_COLOR_CYCLE_SPEED, MAX_COLOR_CYCLE_SPEED);
+        });
+
+    let on_input_color_cycle_phase =
+        use_callback(move |event: Event<FormData>| {
+            let phase = if let Ok(phase) = event.parsed::<f32>() {
+                phase
+            } else {
+                return;
+            };
+            display_params.write().color_cycle_phase =
+                phase.clamp(MIN_COLOR_CYCLE_PHASE, MAX_COLOR_CYCLE_PHASE);
+        });
+
+    let on_input_background_style =
+        use_callback(move |event: Event<FormData>| {
+            let style = if let Some(style) =
+                BackgroundStyle::from_name(&event.value())
+            {
+                style
+            } else {
+                return;
+            };
+            display_params.write().background.style = style;
+        });
+
+    let on_input_background_color =
+        use_callback(move |event: Event<FormData>| {
+            let color = if let Some(color) = Color::parse_hex(&event.value()) {
+                color
+            } else {
+                return;
+            };
+            display_params.write().background.color = color;
+        });
+
+    let on_input_background_end_color =
+        use_callback(move |event: Event<FormData>| {
+            let color = if let Some(color) = Color::parse_hex(&event.value()) {
+                color
+            } else {
+                return;
+            };
+            display_params.write().background.end_color = color;
+        });
+
+    let on_input_background_angle =
+        use_callback(move |event: Event<FormData>| {
+            let angle = if let Ok(angle) = event.parsed::<f32>() {
+                angle
+            } else {
+                return;
+            };
+            display_params.write().background.angle =
+                angle.clamp(MIN_BACKGROUND_ANGLE, MAX_BACKGROUND_ANGLE);
+        });
+
+    let on_input_trail_fade = use_callback(move |event: Event<FormData>| {
+        let trail_fade = if let Ok(trail_fade) = event.parsed::<f32>() {
+            trail_fade
+        } else {
+            return;
         };
-        read_config();
-        let window = web_sys::window().unwrap();
-        let on_pop_state =
-            Closure::<dyn FnMut(web_sys::PopStateEvent)>::new(move |_event| {
-                read_config();
-            });
-        window
-            .add_event_listener_with_callback(
-                "popstate",
-                on_pop_state.as_ref().unchecked_ref(),
-            )
-            .unwrap();
-        on_pop_state.forget(); // FIXME: don't leak
+        display_params.write().trail_fade =
+            trail_fade.clamp(MIN_TRAIL_FADE, MAX_TRAIL_FADE);
     });
 
-    // write config to URL
-    let mut history_push_state_timeout_handle = use_signal(|| None::<i32>);
-    use_effect(move || {
-        let window = web_sys::window().unwrap();
-        let url =
-            web_sys::Url::new(&window.location().href().unwrap()).unwrap();
-        let config_str = config_str();
-        if url
-            .search_params()
-            .get(CONFIG_QUERY_PARAM)
-            .is_some_and(|q| q == config_str)
+    let on_input_blend_mode = use_callback(move |event: Event<FormData>| {
+        let blend_mode =
+            if let Some(blend_mode) = BlendMode::from_name(&event.value()) {
+                blend_mode
+            } else {
+                return;
+            };
+        display_params.write().blend_mode = blend_mode;
+    });
+
+    let on_input_blend_space = use_callback(move |event: Event<FormData>| {
+        let blend_space =
+            if let Some(blend_space) = BlendSpace::from_name(&event.value()) {
+                blend_space
+            } else {
+                return;
+            };
+        display_params.write().blend_space = blend_space;
+    });
+
+    let on_input_trail_style = use_callback(move |event: Event<FormData>| {
+        let trail_style =
+            if let Some(trail_style) = TrailStyle::from_name(&event.value()) {
+                trail_style
+            } else {
+                return;
+            };
+        display_params.write().trail_style = trail_style;
+    });
+
+    let on_input_brush_radius = use_callback(move |event: Event<FormData>| {
+        let brush_radius = if let Ok(brush_radius) = event.parsed::<f32>() {
+            brush_radius
+        } else {
+            return;
+        };
+        display_params.write().brush_radius =
+            brush_radius.clamp(MIN_BRUSH_RADIUS, MAX_BRUSH_RADIUS);
+    });
+
+    let on_input_brush_falloff = use_callback(move |event: Event<FormData>| {
+        let brush_falloff = if let Some(brush_falloff) =
+            BrushFalloff::from_name(&event.value())
         {
+            brush_falloff
+        } else {
             return;
-        }
-        url.search_params()
-            .set(CONFIG_QUERY_PARAM, config_str.as_str());
-        let url = url.href();
-        let history = window.history().unwrap();
-        if let Some(history_push_state_timeout_handle) =
-            *history_push_state_timeout_handle.peek()
+        };
+        display_params.write().brush_falloff = brush_falloff;
+    });
+
+    let on_input_brush_speed_size =
+        use_callback(move |event: Event<FormData>| {
+            let brush_speed_size =
+                if let Ok(brush_speed_size) = event.parsed::<f32>() {
+                    brush_speed_size
+                } else {
+                    return;
+                };
+            display_params.write().brush_speed_size = brush_speed_size
+                .clamp(MIN_BRUSH_SPEED_SIZE, MAX_BRUSH_SPEED_SIZE);
+        });
+
+    let on_input_render_mode = use_callback(move |event: Event<FormData>| {
+        let render_mode =
+            if let Some(render_mode) = RenderMode::from_name(&event.value()) {
+                render_mode
+            } else {
+                return;
+            };
+        display_params.write().render_mode = render_mode;
+    });
+
+    let on_input_tone_mapping = use_callback(move |event: Event<FormData>| {
+        let tone_mapping = if let Some(tone_mapping) =
+            ToneMapping::from_name(&event.value())
         {
-            window.clear_timeout_with_handle(history_push_state_timeout_handle);
-        }
-        let callback = Closure::<dyn FnOnce()>::once_into_js(move || {
-            history
-                .push_state_with_url(&js_sys::Object::new(), "", Some(&url))
-                .unwrap();
+            tone_mapping
+        } else {
+            return;
+        };
+        display_params.write().tone_mapping = tone_mapping;
+    });
+
+    let on_input_exposure = use_callback(move |event: Event<FormData>| {
+        let exposure = if let Ok(exposure) = event.parsed::<f32>() {
+            exposure
+        } else {
+            return;
+        };
+        display_params.write().exposure =
+            exposure.clamp(MIN_EXPOSURE, MAX_EXPOSURE);
+    });
+
+    let on_input_gamma = use_callback(move |event: Event<FormData>| {
+        let gamma = if let Ok(gamma) = event.parsed::<f32>() {
+            gamma
+        } else {
+            return;
+        };
+        display_params.write().gamma = gamma.clamp(MIN_GAMMA, MAX_GAMMA);
+    });
+
+    let on_input_vibrancy = use_callback(move |event: Event<FormData>| {
+        let vibrancy = if let Ok(vibrancy) = event.parsed::<f32>() {
+            vibrancy
+        } else {
+            return;
+        };
+        display_params.write().vibrancy =
+            vibrancy.clamp(MIN_VIBRANCY, MAX_VIBRANCY);
+    });
+
+    let on_input_camera_mode = use_callback(move |event: Event<FormData>| {
+        let mode = if let Some(mode) = CameraMode::from_name(&event.value()) {
+            mode
+        } else {
+            return;
+        };
+        // switching to manual keeps the view where fit or follow left it
+        let mut camera_ = camera.peek().view(&view_camera.peek());
+        camera_.mode = mode;
+        camera.set(camera_);
+    });
+
+    let on_input_camera_zoom = use_callback(move |event: Event<FormData>| {
+        let zoom = if let Ok(zoom) = event.parsed::<f32>() {
+            zoom
+        } else {
+            return;
+        };
+        let mut camera_ = camera.peek().view(&view_camera.peek());
+        camera_.zoom = zoom.clamp(MIN_CAMERA_ZOOM, MAX_CAMERA_ZOOM);
+        camera_.mode = CameraMode::Manual;
+        camera.set(camera_);
+    });
+
+    let on_input_camera_rotation =
+        use_callback(move |event: Event<FormData>| {
+            let rotation = if let Ok(rotation) = event.parsed::<f32>() {
+                rotation
+            } else {
+                return;
+            };
+            camera.write().rotation =
+                rotation.clamp(MIN_CAMERA_ROTATION, MAX_CAMERA_ROTATION);
         });
-        history_push_state_timeout_handle.set(Some(
-            window
-                .set_timeout_with_callback_and_timeout_and_arguments_0(
-                    callback.unchecked_ref(),
-                    CONFIG_COMMIT_DELAY_MS as i32,
-                )
-                .unwrap(),
-        ));
+
+    let on_click_reset_camera = use_callback(move |_: Event<MouseData>| {
+        camera.set(Camera::default());
     });
 
-    // re-render when world updates
-    world.read();
+    let on_wheel_world_canvas = use_callback(move |event: Event<WheelData>| {
+        let (width, height) = if let Some(size) = *world_canvas_size.peek() {
+            (size.width as usize, size.height as usize)
+        } else {
+            return;
+        };
+        event.prevent_default();
+        let dy = event.delta().strip_units().y;
+        let point = event.element_coordinates();
+        let mut camera_ = camera.peek().view(&view_camera.peek());
+        let zoom = camera_.zoom * (-dy / CAMERA_WHEEL_ZOOM_SCALE).exp2() as f32;
+        camera_.zoom_at(
+            zoom.clamp(MIN_CAMERA_ZOOM, MAX_CAMERA_ZOOM),
+            (point.x as f32, point.y as f32),
+            width,
+            height,
+        );
+        camera_.mode = CameraMode::Manual;
+        camera.set(camera_);
+    });
 
-    let SimParams {
-        seed,
-        particle_count,
-        acc_limit,
-    } = &*sim_params.read();
-    let DisplayParams {
-        particle_color_hue_mid,
-        particle_color_hue_spread,
-        particle_color_saturation_mid,
-        particle_color_saturation_spread,
-        particle_color_value,
-        particle_color_alpha,
-    } = &*display_params.read();
+    let on_mouse_down_world_canvas =
+        use_callback(move |event: Event<MouseData>| {
+            let point = event.element_coordinates();
+            camera_drag.set(Some((point.x, point.y)));
+        });
 
-    let world_renderer = world_renderer.read();
-    let world_renderer = world_renderer.as_ref();
-    let paused =
-        world_renderer.is_some_and(|world_renderer| world_renderer.paused());
-    let frame_idx = world_renderer
-        .map(|world_renderer| world_renderer.frame_idx())
-        .unwrap_or(0);
+    let on_mouse_move_world_canvas =
+        use_callback(move |event: Event<MouseData>| {
+            let (x, y) = if let Some(drag) = *camera_drag.peek() {
+                drag
+            } else {
+                return;
+            };
+            let point = event.element_coordinates();
+            camera_drag.set(Some((point.x, point.y)));
+            let mut camera_ = camera.peek().view(&view_camera.peek());
+            camera_.pan((point.x - x) as f32, (point.y - y) as f32);
+            camera_.mode = CameraMode::Manual;
+            camera.set(camera_);
+        });
 
-    let fps = {
-        let now = web_sys::window().unwrap().performance().unwrap().now();
-        let mut frame_times = use_signal(VecDeque::<f64>::new);
-        let mut frame_times = frame_times.write();
-        loop {
-            if let Some(head) = frame_times.front() {
-                if now - head >= 1000.0 {
-                    frame_times.pop_front();
-                    continue;
+    let on_mouse_up_world_canvas = use_callback(move |_: Event<MouseData>| {
+        camera_drag.set(None);
+    });
+
+    let on_input_frame_limit = use_callback(move |event: Event<FormData>| {
+        let frame_limit_ = if let Ok(frame_limit) = event.parsed::<usize>() {
+            frame_limit
+        } else {
+            return;
+        };
+        frame_limit.set(frame_limit_.max(1));
+        if let Some(world_renderer) = &mut *world_renderer.write() {
+            world_renderer.resume();
+        }
+    });
+
+    let on_click_pause_resume = use_callback(move |_: Event<MouseData>| {
+        if let Some(world_renderer) = &mut *world_renderer.write() {
+            world_renderer.pause_resume();
+        }
+    });
+
+    let on_click_reset = use_callback(move |_: Event<MouseData>| {
+        restart(());
+    });
+
+    let on_click_save = use_callback(move |_: Event<MouseData>| {
+        let world_canvas_element = &*world_canvas_element.read();
+        let world_canvas_element =
+            if let Some(world_canvas_element) = world_canvas_element {
+                world_canvas_element
+            } else {
+                return;
+            };
+        let file_name = format!("{}.png", file_name());
+        let document = world_canvas_element.owner_document().unwrap();
+        let closure = Closure::<dyn FnMut(Option<web_sys::Blob>)>::new(
+            move |blob: Option<web_sys::Blob>| {
+                let blob = if let Some(blob) = blob {
+                    blob
+                } else {
+                    return;
+                };
+                download_blob(&document, &blob, &file_name);
+            },
+        );
+        world_canvas_element
+            .to_blob(closure.as_ref().unchecked_ref())
+            .unwrap();
+        closure.forget(); // FIXME: don't leak
+    });
+
+    let on_click_save_svg = use_callback(move |_: Event<MouseData>| {
+        let file_name = format!("{}.svg", file_name());
+        let window = web_sys::window().unwrap();
+        let document = window.document().unwrap();
+        defer(&window, move || {
+            let svg =
+                world.peek().generate_svg(&display_params.peek().background);
+            // TODO: handle errors?
+            let blob = web_sys::Blob::new_with_str_sequence(&vec![svg].into())
+                .unwrap();
+            download_blob(&document, &blob, &file_name);
+        });
+    });
+
+    let on_click_graph = use_callback(move |_: Event<MouseData>| {
+        show_graph.with_mut(|show_graph| {
+            *show_graph = !*show_graph;
+        });
+        if *show_graph.peek() && graph_analysis.peek().is_none() {
+            graph_analysis.set(Some(world.peek().partner_graph().analyze()));
+        }
+    });
+
+    let on_click_save_dot = use_callback(move |_: Event<MouseData>| {
+        let file_name = format!("{}-partners.dot", file_name());
+        let window = web_sys::window().unwrap();
+        let document = window.document().unwrap();
+        defer(&window, move || {
+            let dot = world.peek().partner_graph().generate_dot();
+            let blob = web_sys::Blob::new_with_str_sequence(&vec![dot].into())
+                .unwrap();
+            download_blob(&document, &blob, &file_name);
+        });
+    });
+
+    let on_click_save_graphml = use_callback(move |_: Event<MouseData>| {
+        let file_name = format!("{}-partners.graphml", file_name());
+        let window = web_sys::window().unwrap();
+        let document = window.document().unwrap();
+        defer(&window, move || {
+            let graphml = world.peek().partner_graph().generate_graphml();
+            let blob =
+                web_sys::Blob::new_with_str_sequence(&vec![graphml].into())
+                    .unwrap();
+            download_blob(&document, &blob, &file_name);
+        });
+    });
+
+    let mut stop_explorer = move || {
+        *explorer_generation.write() += 1;
+        explorer_running.set(false);
+    };
+
+    let on_click_explore = use_callback(move |_: Event<MouseData>| {
+        show_explorer.with_mut(|show_explorer| {
+            *show_explorer = !*show_explorer;
+        });
+        if !*show_explorer.peek() {
+            stop_explorer();
+        }
+    });
+
+    let on_input_explorer_seed_count =
+        use_callback(move |event: Event<FormData>| {
+            let seed_count = if let Ok(seed_count) = event.parsed::<usize>() {
+                seed_count
+            } else {
+                return;
+            };
+            explorer_seed_count.set(
+                seed_count
+                    .clamp(MIN_EXPLORER_SEED_COUNT, MAX_EXPLORER_SEED_COUNT),
+            );
+        });
+
+    let on_click_explorer_start_stop =
+        use_callback(move |_: Event<MouseData>| {
+            if *explorer_running.peek() {
+                stop_explorer();
+                return;
+            }
+            *explorer_generation.write() += 1;
+            explorer_running.set(true);
+            explorer_results.write().clear();
+            let generation = *explorer_generation.peek();
+            let seed_count = *explorer_seed_count.peek();
+            spawn(async move {
+                let window = web_sys::window().unwrap();
+                for _ in 0..seed_count {
+                    // let the page render between seeds
+                    next_tick(&window).await;
+                    if *explorer_generation.peek() != generation {
+                        return;
+                    }
+                    let seed = Seed::from_hash(seed_rng.write().gen::<u64>());
+                    let (width, height) = world_canvas_size.peek().map_or(
+                        (EXPLORER_THUMBNAIL_WIDTH, EXPLORER_THUMBNAIL_WIDTH),
+                        |size| (size.width as usize, size.height as usize),
+                    );
+                    let explore_params = ExploreParams {
+                        frame_count: *frame_limit.peek(),
+                        width,
+                        height,
+                        camera: camera.peek().view(&view_camera.peek()),
+                    };
+                    let mut explorer = match SeedExplorer::new(
+                        &sim_params.peek(),
+                        &display_params.peek(),
+                        &explore_params,
+                        seed,
+                    ) {
+                        Ok(explorer) => explorer,
+                        Err(error) => {
+                            warn!("failed to explore seed: {:?}", error);
+                            break;
+                        },
+                    };
+                    // long runs would otherwise freeze the page for the whole
+                    // seed
+                    while !explorer.step(EXPLORER_FRAMES_PER_TICK) {
+                        next_tick(&window).await;
+                        if *explorer_generation.peek() != generation {
+                            return;
+                        }
+                    }
+                    let result = explorer.finish();
+                    let result = result
+                        .image
+                        .scaled(
+                            EXPLORER_THUMBNAIL_WIDTH,
+                            (height * EXPLORER_THUMBNAIL_WIDTH / width).max(1),
+                        )
+                        .encode_png()
+                        .map(|thumbnail| ExplorerResult {
+                            seed: result.seed,
+                            score: result.score,
+                            thumbnail_url: format!(
+                                "data:image/png;base64,{}",
+                                BASE64_STANDARD.encode(thumbnail)
+                            ),
+                        });
+                    let result = match result {
+                        Ok(result) => result,
+                        Err(error) => {
+                            warn!("failed to explore seed: {:?}", error);
+                            break;
+                        },
+                    };
+                    explorer_results.with_mut(|results| {
+                        results.push(result);
+                        results.sort_by(|a, b| {
+                            b.score.score.total_cmp(&a.score.score)
+                        });
+                    });
+                }
+                explorer_running.set(false);
+            });
+        });
+
+    let on_click_explorer_result = use_callback(move |seed: Seed| {
+        sim_params.write().seed = seed;
+    });
+
+    let on_click_export = use_callback(move |_: Event<MouseData>| {
+        show_export.with_mut(|show_export| {
+            *show_export = !*show_export;
+        });
+    });
+
+    let on_input_export_preset = use_callback(move |event: Event<FormData>| {
+        let (width, height) = if let Some((_, width, height)) = EXPORT_PRESETS
+            .iter()
+            .find(|(name, ..)| *name == event.value())
+        {
+            (*width, *height)
+        } else {
+            return;
+        };
+        export_width.set(width);
+        export_height.set(height);
+    });
+
+    let on_input_export_width = use_callback(move |event: Event<FormData>| {
+        let width = if let Ok(width) = event.parsed::<usize>() {
+            width
+        } else {
+            return;
+        };
+        export_width.set(width.clamp(MIN_EXPORT_SIZE, MAX_EXPORT_SIZE));
+    });
+
+    let on_input_export_height = use_callback(move |event: Event<FormData>| {
+        let height = if let Ok(height) = event.parsed::<usize>() {
+            height
+        } else {
+            return;
+        };
+        export_height.set(height.clamp(MIN_EXPORT_SIZE, MAX_EXPORT_SIZE));
+    });
+
+    let on_input_export_source = use_callback(move |event: Event<FormData>| {
+        let source =
+            if let Some(source) = ExportSource::from_name(&event.value()) {
+                source
+            } else {
+                return;
+            };
+        export_source.set(source);
+    });
+
+    let on_click_export_start = use_callback(move |_: Event<MouseData>| {
+        if *exporting.peek() {
+            return;
+        }
+        let (view_width, view_height) =
+            if let Some(size) = *world_canvas_size.peek() {
+                (size.width as usize, size.height as usize)
+            } else {
+                return;
+            };
+        let frame_count = world_renderer
+            .peek()
+            .as_ref()
+            .map_or(0, |world_renderer| world_renderer.frame_idx());
+        let export_params = ExportParams {
+            frame_count,
+            view_width,
+            view_height,
+            width: *export_width.peek(),
+            height: *export_height.peek(),
+            camera: camera.peek().view(&view_camera.peek()),
+            source: *export_source.peek(),
+        };
+        let file_name = format!(
+            "{}-{}x{}.png",
+            file_name(),
+            export_params.width,
+            export_params.height
+        );
+        exporting.set(true);
+        export_error.set(None);
+        spawn(async move {
+            let window = web_sys::window().unwrap();
+            // let the page show the export is running first
+            next_tick(&window).await;
+            let pixel_count = export_params.width * export_params.height;
+            let frames_per_tick =
+                (EXPORT_PIXELS_PER_TICK / pixel_count.max(1)).max(1);
+            let rows_per_tick =
+                (EXPORT_PIXELS_PER_TICK / export_params.width.max(1)).max(1);
+            let png = async {
+                let mut export = Export::new(
+                    &world.peek(),
+                    &sim_params.peek(),
+                    &display_params.peek(),
+                    &export_params,
+                )?;
+                while !export.step(frames_per_tick) {
+                    next_tick(&window).await;
+                }
+                let image = export.finish();
+                let mut encoder = image.png_encoder()?;
+                while !encoder.step(&image, rows_per_tick)? {
+                    next_tick(&window).await;
+                }
+                encoder.finish()
+            }
+            .await;
+            exporting.set(false);
+            let png = match png {
+                Ok(png) => png,
+                Err(error) => {
+                    warn!("failed to export image: {:?}", error);
+                    export_error.set(Some(format!("{error:#}")));
+                    return;
+                },
+            };
+            let blob = web_sys::Blob::new_with_u8_array_sequence(
+                &js_sys::Array::of1(&js_sys::Uint8Array::from(png.as_slice())),
+            )
+            .unwrap();
+            download_blob(&window.document().unwrap(), &blob, &file_name);
+        });
+    });
+
+    let on_click_help = use_callback(move |_: Event<MouseData>| {
+        show_help.with_mut(|show_help| {
+            *show_help = !*show_help;
+        });
+    });
+
+    // changes made live carry on with the same world, anything else starts
+    // the run over
+    use_effect(move || {
+        if world.write().add_events(&sim_params.read()) {
+            return;
+        }
+        restart(());
+    });
+
+    // the display params don't affect the simulation, so the world is only
+    // recolored and the run carries on
+    let mut redraw_trails_timeout_handle = use_signal(|| None::<i32>);
+    use_effect(move || {
+        world.write().recolor(&display_params.read());
+        if !*redraw_trails.peek() {
+            return;
+        }
+        // redrawing the whole history is slow, so it waits for the sliders
+        // to settle
+        let window = web_sys::window().unwrap();
+        if let Some(redraw_trails_timeout_handle) =
+            *redraw_trails_timeout_handle.peek()
+        {
+            window.clear_timeout_with_handle(redraw_trails_timeout_handle);
+        }
+        let callback = Closure::<dyn FnOnce()>::once_into_js(move || {
+            redraw_trails_timeout_handle.set(None);
+            // a truncated history would erase the older trails, those are
+            // only recolored going forward
+            if !world.peek().has_full_history() {
+                return;
+            }
+            if let Some(world_renderer) = &mut *world_renderer.write() {
+                world_renderer.redraw();
+            }
+        });
+        redraw_trails_timeout_handle.set(Some(
+            window
+                .set_timeout_with_callback_and_timeout_and_arguments_0(
+                    callback.unchecked_ref(),
+                    REDRAW_TRAILS_DELAY_MS as i32,
+                )
+                .unwrap(),
+        ));
+    });
+
+    use_effect(move || {
+        let world_canvas_element = &*world_canvas_element.read();
+        let world_canvas_element =
+            if let Some(world_canvas_element) = world_canvas_element {
+                world_canvas_element
+            } else {
+                return;
+            };
+        let world_canvas_size = *world_canvas_size.read();
+        let world_canvas_size =
+            if let Some(world_canvas_size) = world_canvas_size {
+                world_canvas_size
+            } else {
+                return;
+            };
+        world_canvas_element.set_width(world_canvas_size.width as u32);
+        world_canvas_element.set_height(world_canvas_size.height as u32);
+        world_renderer.with_mut(|renderer| {
+            if let Some(renderer) = renderer {
+                renderer.update(world_canvas_element);
+            } else {
+                *renderer = Some(WorldRenderer::new(
+                    world_canvas_element,
+                    world,
+                    display_params,
+                    camera,
+                    view_camera,
+                    frame_limit,
+                ));
+            }
+        });
+    });
+
+    use_effect(move || {
+        camera.read();
+        if let Some(world_renderer) = &mut *world_renderer.write() {
+            world_renderer.redraw_camera();
+        }
+    });
+
+    use_effect(move || {
+        let DisplayParams {
+            particle_color_hue_mid,
+            particle_color_hue_spread,
+            particle_color_saturation_mid,
+            particle_color_saturation_spread,
+            particle_color_value_mid,
+            particle_color_alpha_mid,
+            trail_fade: _,
+            blend_mode: _,
+            render_mode: _,
+            tone_mapping: _,
+            exposure: _,
+            gamma: _,
+            vibrancy: _,
+            trail_style: _,
+            brush_radius: _,
+            brush_falloff: _,
+            brush_speed_size: _,
+            background: _,
+            color_mode: _,
+            palette,
+            color_space,
+            blend_space: _,
+            color_cycle_speed: _,
+            color_cycle_phase: _,
+            dither: _,
+            color_distribution: _,
+            particle_color_value_spread,
+            particle_color_alpha_spread,
+        } = &*display_params.read();
+        // speckles the spread of brightness and opacity over the preview,
+        // with the most opaque particles shown fully opaque since the usual
+        // opacities would barely show
+        let value_range = spread_range_within(
+            *particle_color_value_mid,
+            *particle_color_value_spread,
+            MIN_PARTICLE_COLOR_VALUE_MID..=MAX_PARTICLE_COLOR_VALUE_MID,
+        );
+        let alpha_range = spread_range_within(
+            *particle_color_alpha_mid,
+            *particle_color_alpha_spread,
+            MIN_PARTICLE_COLOR_ALPHA_MID..=MAX_PARTICLE_COLOR_ALPHA_MID,
+        );
+        // offsets are in -0.5..0.5
+        let at = |range: &RangeInclusive<f32>, offset: f32| {
+            lerp(offset + 0.5, 0.0, 1.0, *range.start(), *range.end())
+        };
+        let shade = |x: usize, y: usize| {
+            let value = at(&value_range, Dither::BlueNoise.offset(x, y));
+            let alpha = at(
+                &alpha_range,
+                Dither::BlueNoise.offset(x + PALETTE_WIDTH, y),
+            );
+            (value, alpha / alpha_range.end() * 100.0)
+        };
+        let palette_image = &mut *palette_image.write();
+        if palette.mode != PaletteMode::Range {
+            for x in 0..PALETTE_WIDTH {
+                let color = palette
+                    .sample(x as f32 / (PALETTE_WIDTH - 1) as f32)
+                    .unwrap_or(Color::transparent());
+                for y in 0..PALETTE_HEIGHT {
+                    let (_value, alpha) = shade(x, y);
+                    palette_image.put_pixel(
+                        x,
+                        y,
+                        Color {
+                            a: (color.a as f32 * alpha.min(100.0) / 100.0)
+                                as u8,
+                            ..color
+                        },
+                    );
+                }
+            }
+            return;
+        }
+        for y in 0..PALETTE_HEIGHT {
+            for x in 0..PALETTE_WIDTH {
+                let (value, alpha) = shade(x, y);
+                let color = color_space.color(
+                    lerp(
+                        x as f32,
+                        0.0,
+                        (PALETTE_WIDTH - 1) as f32,
+                        particle_color_hue_mid
+                            - particle_color_hue_spread / 2.0,
+                        particle_color_hue_mid
+                            + particle_color_hue_spread / 2.0,
+                    ),
+                    lerp(
+                        y as f32,
+                        (PALETTE_HEIGHT - 1) as f32,
+                        0.0,
+                        particle_color_saturation_mid
+                            - particle_color_saturation_spread / 2.0,
+                        particle_color_saturation_mid
+                            + particle_color_saturation_spread / 2.0,
+                    ),
+                    value,
+                    alpha,
+                );
+                palette_image.put_pixel(x, y, color);
+            }
+        }
+    });
+
+    use_effect(move || {
+        let palette_canvas_element = &*palette_canvas_element.read();
+        if let Some(palette_canvas_element) = palette_canvas_element {
+            let context = palette_canvas_element
+                .get_context("2d")
+                .unwrap()
+                .unwrap()
+                .dyn_into::<web_sys::CanvasRenderingContext2d>()
+                .unwrap();
+            let image_data = palette_image.read().to_image_data();
+            context.put_image_data(&image_data, 0.0, 0.0).unwrap();
+        }
+    });
+
+    // read config from URL
+    use_effect(move || {
+        let mut read_config = move || {
+            let window = web_sys::window().unwrap();
+            let url =
+                web_sys::Url::new(&window.location().href().unwrap()).unwrap();
+            let config = if let Some(config) = url
+                .search_params()
+                .get(CONFIG_QUERY_PARAM)
+                .as_deref()
+                .and_then(decode_config_str)
+            {
+                config
+            } else {
+                return;
+            };
+            debug!("config from URL: {:#?}", config);
+            let Config {
+                version,
+                sim_params: sim_params_,
+                display_params: display_params_,
+                frame_limit: frame_limit_,
+                camera: camera_,
+            } = config;
+            if version != CONFIG_VERSION {
+                return;
+            }
+            sim_params.set(sim_params_);
+            display_params.set(display_params_);
+            frame_limit.set(frame_limit_);
+            camera.set(camera_);
+        };
+        read_config();
+        let window = web_sys::window().unwrap();
+        let on_pop_state =
+            Closure::<dyn FnMut(web_sys::PopStateEvent)>::new(move |_event| {
+                read_config();
+            });
+        window
+            .add_event_listener_with_callback(
+                "popstate",
+                on_pop_state.as_ref().unchecked_ref(),
+            )
+            .unwrap();
+        on_pop_state.forget(); // FIXME: don't leak
+    });
+
+    // write config to URL
+    let mut history_push_state_timeout_handle = use_signal(|| None::<i32>);
+    use_effect(move || {
+        let window = web_sys::window().unwrap();
+        let url =
+            web_sys::Url::new(&window.location().href().unwrap()).unwrap();
+        let config_str = config_str();
+        if url
+            .search_params()
+            .get(CONFIG_QUERY_PARAM)
+            .is_some_and(|q| q == config_str)
+        {
+            return;
+        }
+        url.search_params()
+            .set(CONFIG_QUERY_PARAM, config_str.as_str());
+        let url = url.href();
+        let history = window.history().unwrap();
+        if let Some(history_push_state_timeout_handle) =
+            *history_push_state_timeout_handle.peek()
+        {
+            window.clear_timeout_with_handle(history_push_state_timeout_handle);
+        }
+        let callback = Closure::<dyn FnOnce()>::once_into_js(move || {
+            history
+                .push_state_with_url(&js_sys::Object::new(), "", Some(&url))
+                .unwrap();
+        });
+        history_push_state_timeout_handle.set(Some(
+            window
+                .set_timeout_with_callback_and_timeout_and_arguments_0(
+                    callback.unchecked_ref(),
+                    CONFIG_COMMIT_DELAY_MS as i32,
+                )
+                .unwrap(),
+        ));
+    });
+
+    // re-render when world updates
+    world.read();
+
+    let SimParams {
+        seed,
+        particle_count,
+        acc_limit,
+        partners,
+        speed_limit,
+        events,
+    } = &*sim_params.read();
+    // live changes show the limits the run is at now
+    let (acc_limit, speed_limit) = if *live_changes.read() {
+        let world = world.peek();
+        (world.acc_limit(), world.speed_limit())
+    } else {
+        (*acc_limit, *speed_limit)
+    };
+    let DisplayParams {
+        particle_color_hue_mid,
+        particle_color_hue_spread,
+        particle_color_saturation_mid,
+        particle_color_saturation_spread,
+        particle_color_value_mid,
+        particle_color_alpha_mid,
+        trail_fade,
+        blend_mode,
+        render_mode,
+        tone_mapping,
+        exposure,
+        gamma,
+        vibrancy,
+        trail_style,
+        brush_radius,
+        brush_falloff,
+        brush_speed_size,
+        background,
+        color_mode,
+        palette,
+        color_space,
+        blend_space,
+        color_cycle_speed,
+        color_cycle_phase,
+        dither,
+        color_distribution,
+        particle_color_value_spread,
+        particle_color_alpha_spread,
+    } = &*display_params.read();
+
+    // the oklch ranges map onto the same params
+    let (saturation_label, value_label) = match color_space {
+        ColorSpace::Hsv => ("saturation", "brightness"),
+        ColorSpace::Oklch => ("chroma", "lightness"),
+    };
+
+    let Camera {
+        center_x: _,
+        center_y: _,
+        zoom: camera_zoom,
+        rotation: camera_rotation,
+        mode: camera_mode,
+    } = &camera.read().view(&view_camera.read());
+
+    let world_renderer = world_renderer.read();
+    let world_renderer = world_renderer.as_ref();
+    let paused =
+        world_renderer.is_some_and(|world_renderer| world_renderer.paused());
+    let frame_idx = world_renderer
+        .map(|world_renderer| world_renderer.frame_idx())
+        .unwrap_or(0);
+
+    let fps = {
+        let now = web_sys::window().unwrap().performance().unwrap().now();
+        let mut frame_times = use_signal(VecDeque::<f64>::new);
+        let mut frame_times = frame_times.write();
+        loop {
+            if let Some(head) = frame_times.front() {
+                if now - head >= 1000.0 {
+                    frame_times.pop_front();
+                    continue;
+                }
+            }
+            break;
+        }
+        frame_times.push_back(now);
+        frame_times.len() as f64
+    };
+
+    rsx! {
+        canvas {
+            class: "world",
+            onmounted: on_world_canvas_mounted,
+            onwheel: on_wheel_world_canvas,
+            onmousedown: on_mouse_down_world_canvas,
+            onmousemove: on_mouse_move_world_canvas,
+            onmouseup: on_mouse_up_world_canvas,
+            onmouseleave: on_mouse_up_world_canvas,
+        }
+        div {
+            class: "ui",
+            div {
+                class: "param seed",
+                div {
+                    class: "param-label",
+                    "seed: "
+                }
+                div {
+                    class: "param-control",
+                    input {
+                        r#type: "text",
+                        value: seed.as_str(),
+                        oninput: on_input_seed,
+                    }
+                }
+                div {
+                    class: "param-control",
+                    button {
+                        onclick: on_click_rand_seed,
+                        "rand"
+                    }
+                }
+            }
+            div {
+                class: "param particle-count",
+                div {
+                    class: "param-label",
+                    "particles: "
+                }
+                div {
+                    class: "param-control",
+                    input {
+                        r#type: "number",
+                        min: MIN_PARTICLE_COUNT,
+                        max: MAX_PARTICLE_COUNT,
+                        value: *particle_count,
+                        disabled: partners.is_some(),
+                        oninput: on_input_particle_count,
+                    }
+                }
+            }
+            div {
+                class: "param acc-limit",
+                div {
+                    class: "param-label",
+                    "acc limit: "
+                }
+                div {
+                    class: "param-control",
+                    "2^"
+                    input {
+                        r#type: "number",
+                        min: MIN_ACC_LIMIT,
+                        max: MAX_ACC_LIMIT,
+                        value: acc_limit,
+                        oninput: on_input_acc_limit,
+                    }
+                }
+            }
+            div {
+                class: "param speed-limit",
+                div {
+                    class: "param-label",
+                    "speed limit: "
+                }
+                div {
+                    class: "param-control",
+                    "2^"
+                    input {
+                        r#type: "number",
+                        min: MIN_SPEED_LIMIT,
+                        max: MAX_SPEED_LIMIT,
+                        value: speed_limit,
+                        oninput: on_input_speed_limit,
+                    }
+                }
+            }
+            div {
+                class: "param live-changes",
+                div {
+                    class: "param-label",
+                    "change live: "
+                }
+                div {
+                    class: "param-control",
+                    input {
+                        r#type: "checkbox",
+                        checked: *live_changes.read(),
+                        oninput: on_input_live_changes,
+                    }
+                }
+            }
+            if !events.is_empty() {
+                div {
+                    class: "param events",
+                    div {
+                        class: "param-label",
+                        "changes: "
+                    }
+                    div {
+                        class: "param-control",
+                        "{events.len()} "
+                        button {
+                            onclick: on_click_clear_events,
+                            "clear"
+                        }
+                    }
+                }
+            }
+            div {
+                class: "param partners",
+                div {
+                    class: "param-label",
+                    "partners: "
+                }
+                if partners.is_some() {
+                    div {
+                        class: "param-value",
+                        "imported"
+                    }
+                    div {
+                        class: "param-control",
+                        button {
+                            onclick: on_click_clear_partners,
+                            "clear"
+                        }
+                    }
+                } else {
+                    div {
+                        class: "param-value",
+                        "seeded"
+                    }
+                }
+                div {
+                    class: "param-control",
+                    input {
+                        r#type: "file",
+                        accept: ".csv,.dot,.gv,.txt",
+                        oninput: on_input_partners_file,
+                    }
+                }
+            }
+            if let Some(partners_error) = &*partners_error.read() {
+                div {
+                    class: "param-error",
+                    "{partners_error}"
+                }
+            }
+            div {
+                class: "param palette-mode",
+                div {
+                    class: "param-label",
+                    "palette: "
+                }
+                div {
+                    class: "param-control",
+                    select {
+                        oninput: on_input_palette_mode,
+                        for mode in PaletteMode::ALL {
+                            option {
+                                value: mode.name(),
+                                selected: *mode == palette.mode,
+                                {mode.name()}
+                            }
+                        }
+                    }
+                }
+            }
+            if palette.mode == PaletteMode::Range {
+                div {
+                    class: "param color-space",
+                    div {
+                        class: "param-label",
+                        "color space: "
+                    }
+                    div {
+                        class: "param-control",
+                        select {
+                            oninput: on_input_color_space,
+                            for space in ColorSpace::ALL {
+                                option {
+                                    value: space.name(),
+                                    selected: space == color_space,
+                                    {space.name()}
+                                }
+                            }
+                        }
+                    }
+                }
+                div {
+                    class: "param particle-color-hue-mid",
+                    div {
+                        class: "param-label",
+                        "hue mid: "
+                    }
+                    div {
+                        class: "param-control",
+                        input {
+                            r#type: "number",
+                            min: MIN_PARTICLE_COLOR_HUE_MID,
+                            max: MAX_PARTICLE_COLOR_HUE_MID,
+                            value: *particle_color_hue_mid,
+                            oninput: on_input_particle_color_hue_mid,
+                        }
+                    }
+                }
+                div {
+                    class: "param particle-color-hue-spread",
+                    div {
+                        class: "param-label",
+                        "hue spread: "
+                    }
+                    div {
+                        class: "param-control",
+                        input {
+                            r#type: "number",
+                            min: MIN_PARTICLE_COLOR_HUE_SPREAD,
+                            max: MAX_PARTICLE_COLOR_HUE_SPREAD,
+                            value: *particle_color_hue_spread,
+                            oninput: on_input_particle_color_hue_spread,
+                        }
+                    }
+                }
+                div {
+                    class: "param particle-color-saturation-mid",
+                    div {
+                        class: "param-label",
+                        "{saturation_label} mid: "
+                    }
+                    div {
+                        class: "param-control",
+                        input {
+                            r#type: "number",
+                            min: MIN_PARTICLE_COLOR_SATURATION_MID,
+                            max: MAX_PARTICLE_COLOR_SATURATION_MID,
+                            value: *particle_color_saturation_mid,
+                            oninput: on_input_particle_color_saturation_mid,
+                        }
+                    }
+                }
+                div {
+                    class: "param particle-color-saturation-spread",
+                    div {
+                        class: "param-label",
+                        "{saturation_label} spread: "
+                    }
+                    div {
+                        class: "param-control",
+                        input {
+                            r#type: "number",
+                            min: MIN_PARTICLE_COLOR_SATURATION_SPREAD,
+                            max: MAX_PARTICLE_COLOR_SATURATION_SPREAD,
+                            value: *particle_color_saturation_spread,
+                            oninput: on_input_particle_color_saturation_spread,
+                        }
+                    }
+                }
+                div {
+                    class: "param particle-color-value-mid",
+                    div {
+                        class: "param-label",
+                        "{value_label} mid: "
+                    }
+                    div {
+                        class: "param-control",
+                        input {
+                            r#type: "number",
+                            min: MIN_PARTICLE_COLOR_VALUE_MID,
+                            max: MAX_PARTICLE_COLOR_VALUE_MID,
+                            value: *particle_color_value_mid,
+                            oninput: on_input_particle_color_value_mid,
+                        }
+                    }
+                }
+                div {
+                    class: "param particle-color-value-spread",
+                    div {
+                        class: "param-label",
+                        "{value_label} spread: "
+                    }
+                    div {
+                        class: "param-control",
+                        input {
+                            r#type: "number",
+                            min: MIN_PARTICLE_COLOR_VALUE_SPREAD,
+                            max: MAX_PARTICLE_COLOR_VALUE_SPREAD,
+                            value: *particle_color_value_spread,
+                            oninput: on_input_particle_color_value_spread,
+                        }
+                    }
+                }
+            }
+            if palette.mode == PaletteMode::Gradient {
+                for (idx, stop) in palette.stops.iter().enumerate() {
+                    div {
+                        class: "param gradient-stop",
+                        div {
+                            class: "param-label",
+                            "stop: "
+                        }
+                        div {
+                            class: "param-control",
+                            input {
+                                r#type: "color",
+                                value: rgb_hex(stop.color),
+                                oninput: move |event| {
+                                    on_input_gradient_stop_color((idx, event))
+                                },
+                            }
+                        }
+                        div {
+                            class: "param-control",
+                            input {
+                                r#type: "number",
+                                min: 0,
+                                max: 100,
+                                value: stop.position * 100.0,
+                                oninput: move |event| {
+                                    on_input_gradient_stop_position((
+                                        idx, event,
+                                    ))
+                                },
+                            }
+                            "%"
+                        }
+                        div {
+                            class: "param-control",
+                            button {
+                                disabled: palette.stops.len() <= 1,
+                                onclick: move |_| {
+                                    on_click_remove_gradient_stop(idx)
+                                },
+                                "remove"
+                            }
+                        }
+                    }
+                }
+                div {
+                    class: "control",
+                    button {
+                        onclick: on_click_add_gradient_stop,
+                        "add stop"
+                    }
+                }
+            }
+            if palette.mode == PaletteMode::Named {
+                div {
+                    class: "param named-palette",
+                    div {
+                        class: "param-label",
+                        "name: "
+                    }
+                    div {
+                        class: "param-control",
+                        select {
+                            oninput: on_input_named_palette,
+                            for named in NamedPalette::ALL {
+                                option {
+                                    value: named.name(),
+                                    selected: *named == palette.named,
+                                    {named.name()}
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            if palette.mode == PaletteMode::Swatches {
+                div {
+                    class: "param swatches",
+                    div {
+                        class: "param-label",
+                        "swatches: "
+                    }
+                    for (idx, swatch) in palette.swatches.iter().enumerate() {
+                        div {
+                            class: "param-control swatch",
+                            input {
+                                r#type: "color",
+                                value: rgb_hex(*swatch),
+                                oninput: move |event| {
+                                    on_input_swatch((idx, event))
+                                },
+                            }
+                            input {
+                                r#type: "number",
+                                min: MIN_SWATCH_WEIGHT,
+                                max: MAX_SWATCH_WEIGHT,
+                                step: 0.1,
+                                title: "weight",
+                                value: palette.swatch_weight(idx),
+                                oninput: move |event| {
+                                    on_input_swatch_weight((idx, event))
+                                },
+                            }
+                            button {
+                                disabled: palette.swatches.len() <= 1,
+                                onclick: move |_| on_click_remove_swatch(idx),
+                                "×"
+                            }
+                        }
+                    }
+                    div {
+                        class: "param-control",
+                        button {
+                            onclick: on_click_add_swatch,
+                            "add"
+                        }
+                    }
+                }
+            }
+            div {
+                class: "param palette-image",
+                div {
+                    class: "param-label",
+                    "palette from image: "
+                }
+                div {
+                    class: "param-control",
+                    input {
+                        r#type: "file",
+                        accept: ".png",
+                        oninput: on_input_palette_image,
+                    }
+                }
+            }
+            if let Some(palette_error) = &*palette_error.read() {
+                div {
+                    class: "param-error",
+                    "{palette_error}"
+                }
+            }
+            div {
+                class: "param particle-color-alpha-mid",
+                div {
+                    class: "param-label",
+                    "opacity mid: "
+                }
+                div {
+                    class: "param-control",
+                    input {
+                        r#type: "number",
+                        min: MIN_PARTICLE_COLOR_ALPHA_MID,
+                        max: MAX_PARTICLE_COLOR_ALPHA_MID,
+                        value: *particle_color_alpha_mid,
+                        oninput: on_input_particle_color_alpha_mid,
+                    }
+                }
+            }
+            div {
+                class: "param particle-color-alpha-spread",
+                div {
+                    class: "param-label",
+                    "opacity spread: "
+                }
+                div {
+                    class: "param-control",
+                    input {
+                        r#type: "number",
+                        min: MIN_PARTICLE_COLOR_ALPHA_SPREAD,
+                        max: MAX_PARTICLE_COLOR_ALPHA_SPREAD,
+                        value: *particle_color_alpha_spread,
+                        oninput: on_input_particle_color_alpha_spread,
+                    }
+                }
+            }
+            div {
+                class: "param particle-color-palette",
+                div {
+                    class: "param-label",
+                    "colors: "
+                }
+                div {
+                    class: "param-value",
+                    canvas {
+                        width: PALETTE_WIDTH,
+                        height: PALETTE_HEIGHT,
+                        onmounted: on_palette_canvas_mounted,
+                    }
+                }
+            }
+            div {
+                class: "param color-mode",
+                div {
+                    class: "param-label",
+                    "color by: "
+                }
+                div {
+                    class: "param-control",
+                    select {
+                        oninput: on_input_color_mode,
+                        for mode in ColorMode::ALL {
+                            option {
+                                value: mode.name(),
+                                selected: mode == color_mode,
+                                {mode.name()}
+                            }
+                        }
+                    }
+                }
+            }
+            if *color_mode == ColorMode::Random {
+                div {
+                    class: "param color-distribution",
+                    div {
+                        class: "param-label",
+                        "spread colors: "
+                    }
+                    div {
+                        class: "param-control",
+                        select {
+                            oninput: on_input_color_distribution,
+                            for distribution in ColorDistribution::ALL {
+                                option {
+                                    value: distribution.name(),
+                                    selected: distribution == color_distribution,
+                                    {distribution.name()}
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            div {
+                class: "param color-cycle-speed",
+                div {
+                    class: "param-label",
+                    "color cycle: "
+                }
+                div {
+                    class: "param-control",
+                    input {
+                        r#type: "number",
+                        min: MIN_COLOR_CYCLE_SPEED,
+                        max: MAX_COLOR_CYCLE_SPEED,
+                        step: 0.1,
+                        value: *color_cycle_speed,
+                        oninput: on_input_color_cycle_speed,
+                    }
+                    "°/frame"
+                }
+            }
+            div {
+                class: "param color-cycle-phase",
+                div {
+                    class: "param-label",
+                    "cycle phase: "
+                }
+                div {
+                    class: "param-control",
+                    input {
+                        r#type: "number",
+                        min: MIN_COLOR_CYCLE_PHASE,
+                        max: MAX_COLOR_CYCLE_PHASE,
+                        value: *color_cycle_phase,
+                        oninput: on_input_color_cycle_phase,
+                    }
+                    "°"
+                }
+            }
+            div {
+                class: "param background-style",
+                div {
+                    class: "param-label",
+                    "background: "
+                }
+                div {
+                    class: "param-control",
+                    select {
+                        oninput: on_input_background_style,
+                        for style in BackgroundStyle::ALL {
+                            option {
+                                value: style.name(),
+                                selected: *style == background.style,
+                                {style.name()}
+                            }
+                        }
+                    }
+                }
+                if background.style != BackgroundStyle::Transparent {
+                    div {
+                        class: "param-control",
+                        input {
+                            r#type: "color",
+                            value: rgb_hex(background.color),
+                            oninput: on_input_background_color,
+                        }
+                    }
+                }
+                if matches!(
+                    background.style,
+                    BackgroundStyle::Linear | BackgroundStyle::Radial
+                ) {
+                    div {
+                        class: "param-control",
+                        input {
+                            r#type: "color",
+                            value: rgb_hex(background.end_color),
+                            oninput: on_input_background_end_color,
+                        }
+                    }
+                }
+            }
+            if background.style == BackgroundStyle::Linear {
+                div {
+                    class: "param background-angle",
+                    div {
+                        class: "param-label",
+                        "gradient angle: "
+                    }
+                    div {
+                        class: "param-control",
+                        input {
+                            r#type: "number",
+                            min: MIN_BACKGROUND_ANGLE,
+                            max: MAX_BACKGROUND_ANGLE,
+                            value: background.angle,
+                            oninput: on_input_background_angle,
+                        }
+                        "°"
+                    }
                 }
             }
-            break;
-        }
-        frame_times.push_back(now);
-        frame_times.len() as f64
-    };
-
-    rsx! {
-        canvas {
-            class: "world",
-            onmounted: on_world_canvas_mounted,
-        }
-        div {
-            class: "ui",
             div {
-                class: "param seed",
+                class: "param trail-fade",
                 div {
                     class: "param-label",
-                    "seed: "
+                    "trail fade: "
                 }
                 div {
                     class: "param-control",
                     input {
-                        r#type: "text",
-                        value: seed.as_str(),
-                        oninput: on_input_seed,
+                        r#type: "number",
+                        min: MIN_TRAIL_FADE,
+                        max: MAX_TRAIL_FADE,
+                        step: 0.1,
+                        value: *trail_fade,
+                        oninput: on_input_trail_fade,
                     }
+                    "%"
+                }
+            }
+            div {
+                class: "param blend-mode",
+                div {
+                    class: "param-label",
+                    "blend: "
                 }
                 div {
                     class: "param-control",
-                    button {
-                        onclick: on_click_rand_seed,
-                        "rand"
+                    select {
+                        oninput: on_input_blend_mode,
+                        for mode in BlendMode::ALL {
+                            option {
+                                value: mode.name(),
+                                selected: mode == blend_mode,
+                                {mode.name()}
+                            }
+                        }
                     }
                 }
             }
             div {
-                class: "param particle-count",
+                class: "param blend-space",
                 div {
                     class: "param-label",
-                    "particles: "
+                    "blend in: "
                 }
                 div {
                     class: "param-control",
-                    input {
-                        r#type: "number",
-                        min: MIN_PARTICLE_COUNT,
-                        max: MAX_PARTICLE_COUNT,
-                        value: *particle_count,
-                        oninput: on_input_particle_count,
+                    select {
+                        oninput: on_input_blend_space,
+                        for space in BlendSpace::ALL {
+                            option {
+                                value: space.name(),
+                                selected: space == blend_space,
+                                {space.name()}
+                            }
+                        }
                     }
                 }
             }
             div {
-                class: "param acc-limit",
+                class: "param trail-style",
                 div {
                     class: "param-label",
-                    "acc limit: "
+                    "trails: "
                 }
                 div {
                     class: "param-control",
-                    "2^"
-                    input {
-                        r#type: "number",
-                        min: MIN_ACC_LIMIT,
-                        max: MAX_ACC_LIMIT,
-                        value: *acc_limit,
-                        oninput: on_input_acc_limit,
+                    select {
+                        oninput: on_input_trail_style,
+                        for style in TrailStyle::ALL {
+                            option {
+                                value: style.name(),
+                                selected: style == trail_style,
+                                {style.name()}
+                            }
+                        }
                     }
                 }
             }
             div {
-                class: "param particle-color-hue-mid",
+                class: "param brush-radius",
                 div {
                     class: "param-label",
-                    "hue mid: "
+                    "brush radius: "
                 }
                 div {
                     class: "param-control",
                     input {
                         r#type: "number",
-                        min: MIN_PARTICLE_COLOR_HUE_MID,
-                        max: MAX_PARTICLE_COLOR_HUE_MID,
-                        value: *particle_color_hue_mid,
-                        oninput: on_input_particle_color_hue_mid,
+                        min: MIN_BRUSH_RADIUS,
+                        max: MAX_BRUSH_RADIUS,
+                        step: 0.1,
+                        value: *brush_radius,
+                        oninput: on_input_brush_radius,
                     }
                 }
             }
             div {
-                class: "param particle-color-hue-spread",
+                class: "param brush-falloff",
                 div {
                     class: "param-label",
-                    "hue spread: "
+                    "brush shape: "
                 }
                 div {
                     class: "param-control",
-                    input {
-                        r#type: "number",
-                        min: MIN_PARTICLE_COLOR_HUE_SPREAD,
-                        max: MAX_PARTICLE_COLOR_HUE_SPREAD,
-                        value: *particle_color_hue_spread,
-                        oninput: on_input_particle_color_hue_spread,
+                    select {
+                        oninput: on_input_brush_falloff,
+                        for falloff in BrushFalloff::ALL {
+                            option {
+                                value: falloff.name(),
+                                selected: falloff == brush_falloff,
+                                {falloff.name()}
+                            }
+                        }
                     }
                 }
             }
             div {
-                class: "param particle-color-saturation-mid",
+                class: "param brush-speed-size",
                 div {
                     class: "param-label",
-                    "saturation mid: "
+                    "brush speed size: "
                 }
                 div {
                     class: "param-control",
                     input {
                         r#type: "number",
-                        min: MIN_PARTICLE_COLOR_SATURATION_MID,
-                        max: MAX_PARTICLE_COLOR_SATURATION_MID,
-                        value: *particle_color_saturation_mid,
-                        oninput: on_input_particle_color_saturation_mid,
+                        min: MIN_BRUSH_SPEED_SIZE,
+                        max: MAX_BRUSH_SPEED_SIZE,
+                        step: 0.1,
+                        value: *brush_speed_size,
+                        oninput: on_input_brush_speed_size,
                     }
                 }
             }
             div {
-                class: "param particle-color-saturation-spread",
+                class: "param render-mode",
                 div {
                     class: "param-label",
-                    "saturation spread: "
+                    "render: "
                 }
                 div {
                     class: "param-control",
-                    input {
-                        r#type: "number",
-                        min: MIN_PARTICLE_COLOR_SATURATION_SPREAD,
-                        max: MAX_PARTICLE_COLOR_SATURATION_SPREAD,
-                        value: *particle_color_saturation_spread,
-                        oninput: on_input_particle_color_saturation_spread,
+                    select {
+                        oninput: on_input_render_mode,
+                        for mode in RenderMode::ALL {
+                            option {
+                                value: mode.name(),
+                                selected: mode == render_mode,
+                                {mode.name()}
+                            }
+                        }
+                    }
+                }
+            }
+            if *render_mode == RenderMode::Accumulate {
+                div {
+                    class: "param tone-mapping",
+                    div {
+                        class: "param-label",
+                        "tone mapping: "
+                    }
+                    div {
+                        class: "param-control",
+                        select {
+                            oninput: on_input_tone_mapping,
+                            for mapping in ToneMapping::ALL {
+                                option {
+                                    value: mapping.name(),
+                                    selected: mapping == tone_mapping,
+                                    {mapping.name()}
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            if *render_mode == RenderMode::Density {
+                div {
+                    class: "param vibrancy",
+                    div {
+                        class: "param-label",
+                        "vibrancy: "
+                    }
+                    div {
+                        class: "param-control",
+                        input {
+                            r#type: "number",
+                            min: MIN_VIBRANCY,
+                            max: MAX_VIBRANCY,
+                            value: *vibrancy,
+                            oninput: on_input_vibrancy,
+                        }
+                    }
+                }
+            }
+            if *render_mode != RenderMode::Direct {
+                div {
+                    class: "param exposure",
+                    div {
+                        class: "param-label",
+                        "exposure: "
+                    }
+                    div {
+                        class: "param-control",
+                        input {
+                            r#type: "number",
+                            min: MIN_EXPOSURE,
+                            max: MAX_EXPOSURE,
+                            step: 0.1,
+                            value: *exposure,
+                            oninput: on_input_exposure,
+                        }
+                    }
+                }
+                div {
+                    class: "param gamma",
+                    div {
+                        class: "param-label",
+                        "gamma: "
+                    }
+                    div {
+                        class: "param-control",
+                        input {
+                            r#type: "number",
+                            min: MIN_GAMMA,
+                            max: MAX_GAMMA,
+                            step: 0.1,
+                            value: *gamma,
+                            oninput: on_input_gamma,
+                        }
+                    }
+                }
+            }
+            div {
+                class: "param dither",
+                div {
+                    class: "param-label",
+                    "dither: "
+                }
+                div {
+                    class: "param-control",
+                    select {
+                        oninput: on_input_dither,
+                        for mode in Dither::ALL {
+                            option {
+                                value: mode.name(),
+                                selected: mode == dither,
+                                {mode.name()}
+                            }
+                        }
                     }
                 }
             }
             div {
-                class: "param particle-color-value",
+                class: "param redraw-trails",
                 div {
                     class: "param-label",
-                    "brightness: "
+                    "redraw trails: "
                 }
                 div {
                     class: "param-control",
                     input {
-                        r#type: "number",
-                        min: MIN_PARTICLE_COLOR_VALUE,
-                        max: MAX_PARTICLE_COLOR_VALUE,
-                        value: *particle_color_value,
-                        oninput: on_input_particle_color_value,
+                        r#type: "checkbox",
+                        checked: *redraw_trails.read(),
+                        oninput: on_input_redraw_trails,
+                    }
+                }
+            }
+            div {
+                class: "param camera-mode",
+                div {
+                    class: "param-label",
+                    "camera: "
+                }
+                div {
+                    class: "param-control",
+                    select {
+                        oninput: on_input_camera_mode,
+                        for mode in CameraMode::ALL {
+                            option {
+                                value: mode.name(),
+                                selected: mode == camera_mode,
+                                {mode.name()}
+                            }
+                        }
+                    }
+                }
+                div {
+                    class: "param-control",
+                    button {
+                        onclick: on_click_reset_camera,
+                        "reset"
                     }
                 }
             }
             div {
-                class: "param particle-color-alpha",
+                class: "param camera-zoom",
                 div {
                     class: "param-label",
-                    "opacity: "
+                    "zoom: "
                 }
                 div {
                     class: "param-control",
                     input {
                         r#type: "number",
-                        min: MIN_PARTICLE_COLOR_ALPHA,
-                        max: MAX_PARTICLE_COLOR_ALPHA,
-                        value: *particle_color_alpha,
-                        oninput: on_input_particle_color_alpha,
+                        min: MIN_CAMERA_ZOOM,
+                        max: MAX_CAMERA_ZOOM,
+                        step: 0.1,
+                        value: *camera_zoom,
+                        oninput: on_input_camera_zoom,
                     }
                 }
             }
             div {
-                class: "param particle-color-palette",
+                class: "param camera-rotation",
                 div {
                     class: "param-label",
-                    "colors: "
+                    "rotation: "
                 }
                 div {
-                    class: "param-value",
-                    canvas {
-                        width: PALETTE_WIDTH,
-                        height: PALETTE_HEIGHT,
-                        onmounted: on_palette_canvas_mounted,
+                    class: "param-control",
+                    input {
+                        r#type: "number",
+                        min: MIN_CAMERA_ROTATION,
+                        max: MAX_CAMERA_ROTATION,
+                        value: *camera_rotation,
+                        oninput: on_input_camera_rotation,
                     }
+                    "°"
                 }
             }
             div {
@@ -787,6 +2790,13 @@ fn App() -> Element {
                     "save png"
                 }
             }
+            div {
+                class: "control",
+                button {
+                    onclick: on_click_export,
+                    "export"
+                }
+            }
             div {
                 class: "control",
                 button {
@@ -794,6 +2804,20 @@ fn App() -> Element {
                     "save svg"
                 }
             }
+            div {
+                class: "control",
+                button {
+                    onclick: on_click_graph,
+                    "graph"
+                }
+            }
+            div {
+                class: "control",
+                button {
+                    onclick: on_click_explore,
+                    "explore"
+                }
+            }
             div {
                 class: "control",
                 button {
@@ -802,6 +2826,42 @@ fn App() -> Element {
                 }
             }
         }
+        if let Some(graph_analysis) =
+            show_graph.read().then(|| graph_analysis.read().clone()).flatten()
+        {
+            GraphPanel {
+                graph_analysis,
+                on_click_close: on_click_graph,
+                on_click_save_dot,
+                on_click_save_graphml,
+            }
+        }
+        if *show_explorer.read() {
+            ExplorerPanel {
+                results: explorer_results.read().clone(),
+                seed_count: *explorer_seed_count.read(),
+                running: *explorer_running.read(),
+                on_input_seed_count: on_input_explorer_seed_count,
+                on_click_start_stop: on_click_explorer_start_stop,
+                on_click_result: on_click_explorer_result,
+                on_click_close: on_click_explore,
+            }
+        }
+        if *show_export.read() {
+            ExportPanel {
+                width: *export_width.read(),
+                height: *export_height.read(),
+                source: *export_source.read(),
+                exporting: *exporting.read(),
+                error: export_error.read().clone(),
+                on_input_preset: on_input_export_preset,
+                on_input_width: on_input_export_width,
+                on_input_height: on_input_export_height,
+                on_input_source: on_input_export_source,
+                on_click_start: on_click_export_start,
+                on_click_close: on_click_export,
+            }
+        }
         if *show_help.read() {
             div {
                 class: "help",
@@ -822,24 +2882,347 @@ fn App() -> Element {
     }
 }
 
-fn encode_config_str(config: Config) -> String {
-    let message_pack = rmp_serde::to_vec(&config).unwrap();
-    let deflated_message_pack = deflate::deflate_bytes_conf(
-        message_pack.as_slice(),
-        deflate::CompressionOptions::high(),
-    );
-    let base64 =
-        BASE64_URL_SAFE_NO_PAD.encode(deflated_message_pack.as_slice());
-    base64
+#[derive(Debug, Clone, PartialEq)]
+struct ExplorerResult {
+    seed: Seed,
+    score: SeedScore,
+    thumbnail_url: String,
+}
+
+#[component]
+fn ExplorerPanel(
+    results: Vec<ExplorerResult>,
+    seed_count: usize,
+    running: bool,
+    on_input_seed_count: Callback<Event<FormData>>,
+    on_click_start_stop: Callback<Event<MouseData>>,
+    on_click_result: Callback<Seed>,
+    on_click_close: Callback<Event<MouseData>>,
+) -> Element {
+    let explored_count = results.len();
+
+    rsx! {
+        div {
+            class: "explorer",
+            button {
+                class: "close",
+                onclick: on_click_close,
+                "close"
+            }
+            h1 { "Seed explorer" }
+            div {
+                class: "param explorer-seed-count",
+                div {
+                    class: "param-label",
+                    "seeds: "
+                }
+                div {
+                    class: "param-control",
+                    input {
+                        r#type: "number",
+                        min: MIN_EXPLORER_SEED_COUNT,
+                        max: MAX_EXPLORER_SEED_COUNT,
+                        value: seed_count,
+                        disabled: running,
+                        oninput: on_input_seed_count,
+                    }
+                }
+                div {
+                    class: "param-control",
+                    button {
+                        onclick: on_click_start_stop,
+                        if running { "stop" } else { "start" }
+                    }
+                }
+                if running {
+                    div {
+                        class: "param-value",
+                        "{explored_count}/{seed_count}"
+                    }
+                }
+            }
+            div {
+                class: "explorer-results",
+                for result in results {
+                    div {
+                        key: "{result.seed.as_str()}",
+                        class: "explorer-result",
+                        title: format!(
+                            "{}\ncoverage: {:.3}\nentropy: {:.3}\nspread: {:.3}",
+                            result.seed.as_str(),
+                            result.score.coverage,
+                            result.score.entropy,
+                            result.score.spread,
+                        ),
+                        onclick: {
+                            let seed = result.seed.clone();
+                            move |_| on_click_result(seed.clone())
+                        },
+                        img {
+                            src: result.thumbnail_url,
+                        }
+                        div {
+                            class: "explorer-score",
+                            "{result.score.score:.3}"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+#[component]
+fn ExportPanel(
+    width: usize,
+    height: usize,
+    source: ExportSource,
+    exporting: bool,
+    error: Option<String>,
+    on_input_preset: Callback<Event<FormData>>,
+    on_input_width: Callback<Event<FormData>>,
+    on_input_height: Callback<Event<FormData>>,
+    on_input_source: Callback<Event<FormData>>,
+    on_click_start: Callback<Event<MouseData>>,
+    on_click_close: Callback<Event<MouseData>>,
+) -> Element {
+    let preset = EXPORT_PRESETS
+        .iter()
+        .find(|(_, preset_width, preset_height)| {
+            (*preset_width, *preset_height) == (width, height)
+        })
+        .map(|(name, ..)| *name);
+
+    rsx! {
+        div {
+            class: "export",
+            button {
+                class: "close",
+                onclick: on_click_close,
+                "close"
+            }
+            h1 { "Export" }
+            div {
+                class: "param export-preset",
+                div {
+                    class: "param-label",
+                    "size: "
+                }
+                div {
+                    class: "param-control",
+                    select {
+                        oninput: on_input_preset,
+                        option {
+                            value: "",
+                            selected: preset.is_none(),
+                            "custom"
+                        }
+                        for (name, _, _) in EXPORT_PRESETS {
+                            option {
+                                value: *name,
+                                selected: preset == Some(*name),
+                                {*name}
+                            }
+                        }
+                    }
+                }
+            }
+            div {
+                class: "param export-width",
+                div {
+                    class: "param-label",
+                    "width: "
+                }
+                div {
+                    class: "param-control",
+                    input {
+                        r#type: "number",
+                        min: MIN_EXPORT_SIZE,
+                        max: MAX_EXPORT_SIZE,
+                        value: width,
+                        oninput: on_input_width,
+                    }
+                }
+            }
+            div {
+                class: "param export-height",
+                div {
+                    class: "param-label",
+                    "height: "
+                }
+                div {
+                    class: "param-control",
+                    input {
+                        r#type: "number",
+                        min: MIN_EXPORT_SIZE,
+                        max: MAX_EXPORT_SIZE,
+                        value: height,
+                        oninput: on_input_height,
+                    }
+                }
+            }
+            div {
+                class: "param export-source",
+                div {
+                    class: "param-label",
+                    "render from: "
+                }
+                div {
+                    class: "param-control",
+                    select {
+                        oninput: on_input_source,
+                        for source_ in ExportSource::ALL {
+                            option {
+                                value: source_.name(),
+                                selected: *source_ == source,
+                                {source_.name()}
+                            }
+                        }
+                    }
+                }
+            }
+            div {
+                class: "control",
+                button {
+                    disabled: exporting,
+                    onclick: on_click_start,
+                    if exporting { "exporting..." } else { "export png" }
+                }
+            }
+            if let Some(error) = error {
+                div {
+                    class: "param-error",
+                    "{error}"
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn GraphPanel(
+    graph_analysis: GraphAnalysis,
+    on_click_close: Callback<Event<MouseData>>,
+    on_click_save_dot: Callback<Event<MouseData>>,
+    on_click_save_graphml: Callback<Event<MouseData>>,
+) -> Element {
+    let GraphAnalysis {
+        node_count,
+        edge_count,
+        component_count,
+        largest_component_size,
+        cyclic_component_count,
+        cyclic_node_count,
+        mutual_pair_count,
+        shortest_cycle,
+        in_degree_counts,
+        leaders,
+    } = graph_analysis;
+
+    let shortest_cycle = shortest_cycle
+        .map_or_else(|| "none found".to_owned(), |len| len.to_string());
+    let in_degree_counts = in_degree_counts
+        .iter()
+        .enumerate()
+        .filter(|(_in_degree, count)| **count > 0)
+        .map(|(in_degree, count)| format!("{in_degree}: {count}"))
+        .collect::<Vec<_>>();
+    let leader_count = leaders.len();
+    let leaders = {
+        let mut s = leaders
+            .iter()
+            .take(GRAPH_LEADERS_SHOWN)
+            .map(|idx| idx.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        if leader_count > GRAPH_LEADERS_SHOWN {
+            s.push_str(", ...");
+        }
+        s
+    };
+
+    rsx! {
+        div {
+            class: "graph",
+            button {
+                onclick: on_click_close,
+                "close"
+            }
+            h1 { "Partner graph" }
+            table {
+                tbody {
+                    tr {
+                        th { "particles" }
+                        td { "{node_count}" }
+                    }
+                    tr {
+                        th { "partner links" }
+                        td { "{edge_count}" }
+                    }
+                    tr {
+                        th { "strongly connected components" }
+                        td { "{component_count}" }
+                    }
+                    tr {
+                        th { "largest component" }
+                        td { "{largest_component_size}" }
+                    }
+                    tr {
+                        th { "components with cycles" }
+                        td { "{cyclic_component_count}" }
+                    }
+                    tr {
+                        th { "particles on cycles" }
+                        td { "{cyclic_node_count}" }
+                    }
+                    tr {
+                        th { "mutual partners" }
+                        td { "{mutual_pair_count}" }
+                    }
+                    tr {
+                        th { "shortest cycle" }
+                        td { "{shortest_cycle}" }
+                    }
+                    tr {
+                        th { "leaders" }
+                        td { "{leader_count}" }
+                    }
+                    if leader_count > 0 {
+                        tr {
+                            th {}
+                            td { class: "leaders", "{leaders}" }
+                        }
+                    }
+                    tr {
+                        th { "in-degrees" }
+                        td {
+                            for in_degree_count in in_degree_counts {
+                                div { "{in_degree_count}" }
+                            }
+                        }
+                    }
+                }
+            }
+            div {
+                class: "control",
+                button {
+                    onclick: on_click_save_dot,
+                    "save dot"
+                }
+                button {
+                    onclick: on_click_save_graphml,
+                    "save graphml"
+                }
+            }
+        }
+    }
 }
 
-fn decode_config_str(s: &str) -> Option<Config> {
-    let base64 = s;
-    let deflated_message_pack = BASE64_URL_SAFE_NO_PAD.decode(base64).ok()?;
-    let message_pack =
-        inflate::inflate_bytes(deflated_message_pack.as_slice()).ok()?;
-    let config = rmp_serde::from_slice(message_pack.as_slice()).ok()?;
-    Some(config)
+// the color as a `#rrggbb` color input value, which has no alpha
+fn rgb_hex(color: Color) -> String {
+    let Color { r, g, b, a: _ } = color;
+    format!("#{r:02x}{g:02x}{b:02x}")
 }
 
 fn download_blob(
@@ -863,6 +3246,13 @@ fn download_url(document: &web_sys::Document, url: &str, file_name: &str) {
     body.remove_child(&anchor).unwrap();
 }
 
+async fn next_tick(window: &web_sys::Window) {
+    let promise = js_sys::Promise::new(&mut |resolve, _reject| {
+        window.set_timeout_with_callback(&resolve).unwrap();
+    });
+    wasm_bindgen_futures::JsFuture::from(promise).await.unwrap();
+}
+
 fn defer(window: &web_sys::Window, body: impl FnMut() + 'static) {
     let closure = Closure::<dyn FnMut()>::new(body);
     window
@@ -89,3 +89,16 @@ pub fn lerp(
 pub fn spread_range(mid: f32, spread: f32) -> RangeInclusive<f32> {
     mid - spread / 2.0..=mid + spread / 2.0
 }
+
+// like `spread_range`, but moved to fit within the bounds, and cut to them
+// when the spread is wider
+pub fn spread_range_within(
+    mid: f32,
+    spread: f32,
+    bounds: RangeInclusive<f32>,
+) -> RangeInclusive<f32> {
+    let (min, max) = bounds.into_inner();
+    let spread = spread.clamp(0.0, max - min);
+    let mid = mid.clamp(min + spread / 2.0, max - spread / 2.0);
+    spread_range(mid, spread)
+}
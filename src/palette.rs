@@ -0,0 +1,376 @@
+use crate::{
+    color::{Color, LinearColor, OkLab},
+    image::Image,
+};
+use anyhow::{ensure, Result};
+use rand::prelude::*;
+use rand_chacha::ChaCha20Rng;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+// enough pixels to find the main colors of any image quickly
+const MAX_EXTRACT_SAMPLES: usize = 16384;
+const MAX_EXTRACT_ITERATIONS: usize = 32;
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize,
+)]
+pub enum PaletteMode {
+    // the hue and saturation ranges
+    #[default]
+    Range,
+    Gradient,
+    Named,
+    Swatches,
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize,
+)]
+pub enum NamedPalette {
+    #[default]
+    Viridis,
+    Magma,
+    Inferno,
+    Plasma,
+    Sunset,
+    Ocean,
+    Fire,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ColorStop {
+    // 0..1 along the gradient
+    pub position: f32,
+    pub color: Color,
+}
+
+// the palette modes other than the range each keep their own colors, so
+// switching between them doesn't lose any
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Palette {
+    pub mode: PaletteMode,
+    pub stops: Vec<ColorStop>,
+    pub named: NamedPalette,
+    pub swatches: Vec<Color>,
+    // how much of the palette each swatch gets relative to the others,
+    // swatches without a weight get 1
+    #[serde(default)]
+    pub swatch_weights: Vec<f32>,
+}
+
+impl PaletteMode {
+    pub const ALL: &[Self] =
+        &[Self::Range, Self::Gradient, Self::Named, Self::Swatches];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Range => "range",
+            Self::Gradient => "gradient",
+            Self::Named => "named",
+            Self::Swatches => "swatches",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|mode| mode.name() == name)
+    }
+}
+
+impl NamedPalette {
+    pub const ALL: &[Self] = &[
+        Self::Viridis,
+        Self::Magma,
+        Self::Inferno,
+        Self::Plasma,
+        Self::Sunset,
+        Self::Ocean,
+        Self::Fire,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Viridis => "viridis",
+            Self::Magma => "magma",
+            Self::Inferno => "inferno",
+            Self::Plasma => "plasma",
+            Self::Sunset => "sunset",
+            Self::Ocean => "ocean",
+            Self::Fire => "fire",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|palette| palette.name() == name)
+    }
+
+    // evenly spaced colors, the matplotlib ones are sampled from the originals
+    fn colors(self) -> &'static [u32] {
+        match self {
+            Self::Viridis => &[
+                0x440154ff, 0x482878ff, 0x3e4989ff, 0x31688eff, 0x26828eff,
+                0x1f9e89ff, 0x35b779ff, 0x6ece58ff, 0xb5de2bff, 0xfde725ff,
+            ],
+            Self::Magma => &[
+                0x000004ff, 0x180f3dff, 0x440f76ff, 0x721f81ff, 0x9e2f7fff,
+                0xcd4071ff, 0xf1605dff, 0xfd9668ff, 0xfeca8dff, 0xfcfdbfff,
+            ],
+            Self::Inferno => &[
+                0x000004ff, 0x1b0c41ff, 0x4a0c6bff, 0x781c6dff, 0xa52c60ff,
+                0xcf4446ff, 0xed6925ff, 0xfb9b06ff, 0xf7d13dff, 0xfcffa4ff,
+            ],
+            Self::Plasma => &[
+                0x0d0887ff, 0x46039fff, 0x7201a8ff, 0x9c179eff, 0xbd3786ff,
+                0xd8576bff, 0xed7953ff, 0xfb9f3aff, 0xfdca26ff, 0xf0f921ff,
+            ],
+            Self::Sunset => &[
+                0x2b1b5aff, 0x6a2c83ff, 0xb0306aff, 0xe8534dff, 0xf98f45ff,
+                0xfcc861ff,
+            ],
+            Self::Ocean => {
+                &[0x03045eff, 0x0077b6ff, 0x00b4d8ff, 0x90e0efff, 0xcaf0f8ff]
+            },
+            Self::Fire => &[
+                0x5a0000ff, 0xa01000ff, 0xe04000ff, 0xff8a10ff, 0xffd060ff,
+                0xfff4c0ff,
+            ],
+        }
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self {
+            mode: PaletteMode::Range,
+            stops: vec![
+                ColorStop {
+                    position: 0.0,
+                    color: Color::hex(0xff3080ff),
+                },
+                ColorStop {
+                    position: 1.0,
+                    color: Color::hex(0x30c0ffff),
+                },
+            ],
+            named: NamedPalette::Viridis,
+            swatches: vec![
+                Color::hex(0xe63946ff),
+                Color::hex(0xf1faeeff),
+                Color::hex(0xa8dadcff),
+                Color::hex(0x457b9dff),
+            ],
+            swatch_weights: Vec::new(),
+        }
+    }
+}
+
+impl Palette {
+    // the color at 0..1 along the palette, none for the range which is
+    // sampled from the hue and saturation ranges instead
+    pub fn sample(&self, t: f32) -> Option<Color> {
+        let t = t.clamp(0.0, 1.0);
+        match self.mode {
+            PaletteMode::Range => None,
+            PaletteMode::Gradient => sample_stops(&self.stops, t),
+            PaletteMode::Named => {
+                let colors = self.named.colors();
+                let last = (colors.len() - 1) as f32;
+                let x = t * last;
+                let idx = (x as usize).min(colors.len() - 2);
+                Some(
+                    Color::hex(colors[idx])
+                        .lerp(Color::hex(colors[idx + 1]), x - idx as f32),
+                )
+            },
+            // swatches don't blend, each gets a share by its weight
+            PaletteMode::Swatches => {
+                let len = self.swatches.len();
+                if len == 0 {
+                    return None;
+                }
+                let total: f32 =
+                    (0..len).map(|idx| self.swatch_weight(idx)).sum();
+                if self.swatch_weights.is_empty() || total <= 0.0 {
+                    return Some(
+                        self.swatches[((t * len as f32) as usize).min(len - 1)],
+                    );
+                }
+                let mut x = t * total;
+                for (idx, swatch) in self.swatches.iter().enumerate() {
+                    let weight = self.swatch_weight(idx);
+                    if x < weight {
+                        return Some(*swatch);
+                    }
+                    x -= weight;
+                }
+                self.swatches.last().copied()
+            },
+        }
+    }
+
+    pub fn swatch_weight(&self, idx: usize) -> f32 {
+        self.swatch_weights
+            .get(idx)
+            .copied()
+            .unwrap_or(1.0)
+            .max(0.0)
+    }
+
+    // swatches of the main colors of a PNG, weighted by how much of it they
+    // cover
+    pub fn from_png(png: &[u8], count: usize) -> Result<Self> {
+        let (_width, _height, pixels) = Image::decode_png(png)?;
+        let swatches = extract_swatches(&pixels, count);
+        ensure!(!swatches.is_empty(), "the image has no opaque pixels");
+        Ok(Self {
+            mode: PaletteMode::Swatches,
+            swatches: swatches.iter().map(|(color, _weight)| *color).collect(),
+            swatch_weights: swatches
+                .iter()
+                .map(|(_color, weight)| *weight)
+                .collect(),
+            ..Self::default()
+        })
+    }
+}
+
+// k-means clustering of the mostly opaque pixels in OKLab, so the clusters
+// are colors that look different, the most common first and weighted so the
+// average weight is 1
+pub fn extract_swatches(pixels: &[Color], count: usize) -> Vec<(Color, f32)> {
+    let opaque = pixels.iter().filter(|pixel| pixel.a >= 128);
+    let step = opaque.clone().count().div_ceil(MAX_EXTRACT_SAMPLES).max(1);
+    let samples = opaque
+        .step_by(step)
+        .map(|&pixel| OkLab::from_linear(LinearColor::from_color(pixel)))
+        .collect::<Vec<_>>();
+    if samples.is_empty() || count == 0 {
+        return Vec::new();
+    }
+
+    // k-means++ with a fixed seed, so an image always gives the same palette
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    let mut centers = vec![samples[rng.gen_range(0..samples.len())]];
+    let mut distances = samples
+        .iter()
+        .map(|sample| distance_sq(*sample, centers[0]))
+        .collect::<Vec<_>>();
+    while centers.len() < count {
+        let total = distances.iter().sum::<f32>();
+        // fewer distinct colors than swatches
+        if total <= 0.0 {
+            break;
+        }
+        let mut x = rng.gen_range(0.0..total);
+        let idx = distances
+            .iter()
+            .position(|&distance| {
+                x -= distance;
+                x < 0.0
+            })
+            .unwrap_or(samples.len() - 1);
+        let center = samples[idx];
+        centers.push(center);
+        for (distance, sample) in distances.iter_mut().zip(&samples) {
+            *distance = distance.min(distance_sq(*sample, center));
+        }
+    }
+
+    let mut assignments = vec![0; samples.len()];
+    for iteration in 0..MAX_EXTRACT_ITERATIONS {
+        let mut changed = false;
+        for (assignment, sample) in assignments.iter_mut().zip(&samples) {
+            let nearest = nearest_center(&centers, *sample);
+            if nearest != *assignment {
+                *assignment = nearest;
+                changed = true;
+            }
+        }
+        if iteration > 0 && !changed {
+            break;
+        }
+        let mut sums = vec![(0.0, 0.0, 0.0, 0usize); centers.len()];
+        for (&assignment, sample) in assignments.iter().zip(&samples) {
+            let sum = &mut sums[assignment];
+            sum.0 += sample.l;
+            sum.1 += sample.a;
+            sum.2 += sample.b;
+            sum.3 += 1;
+        }
+        for (center, (l, a, b, n)) in centers.iter_mut().zip(sums) {
+            if n > 0 {
+                let n = n as f32;
+                *center = OkLab {
+                    l: l / n,
+                    a: a / n,
+                    b: b / n,
+                };
+            }
+        }
+    }
+
+    let mut sizes = vec![0usize; centers.len()];
+    for &assignment in &assignments {
+        sizes[assignment] += 1;
+    }
+    let mut swatches = centers
+        .into_iter()
+        .zip(sizes)
+        .filter(|(_center, size)| *size > 0)
+        .collect::<Vec<_>>();
+    swatches.sort_by(|a, b| b.1.cmp(&a.1));
+    let scale = swatches.len() as f32 / samples.len() as f32;
+    swatches
+        .into_iter()
+        .map(|(center, size)| {
+            (center.to_linear().to_color(), size as f32 * scale)
+        })
+        .collect()
+}
+
+fn distance_sq(a: OkLab, b: OkLab) -> f32 {
+    (a.l - b.l).powi(2) + (a.a - b.a).powi(2) + (a.b - b.b).powi(2)
+}
+
+fn nearest_center(centers: &[OkLab], sample: OkLab) -> usize {
+    centers
+        .iter()
+        .map(|center| distance_sq(*center, sample))
+        .enumerate()
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map_or(0, |(idx, _distance)| idx)
+}
+
+fn sample_stops(stops: &[ColorStop], t: f32) -> Option<Color> {
+    // the stops can be edited into any order
+    let stops = if stops
+        .windows(2)
+        .all(|pair| pair[0].position <= pair[1].position)
+    {
+        Cow::Borrowed(stops)
+    } else {
+        let mut stops = stops.to_vec();
+        stops.sort_by(|a, b| a.position.total_cmp(&b.position));
+        Cow::Owned(stops)
+    };
+    let first = stops.first()?;
+    if t <= first.position {
+        return Some(first.color);
+    }
+    for pair in stops.windows(2) {
+        let [a, b] = pair else { unreachable!() };
+        if t <= b.position {
+            let width = b.position - a.position;
+            let u = if width > 0.0 {
+                (t - a.position) / width
+            } else {
+                1.0
+            };
+            return Some(a.color.lerp(b.color, u));
+        }
+    }
+    stops.last().map(|stop| stop.color)
+}
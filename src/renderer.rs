@@ -1,4 +1,8 @@
-use crate::{color::Color, image::Image, world::World};
+use crate::{
+    camera::Camera,
+    image::Image,
+    world::{DisplayParams, World},
+};
 use dioxus::{logger::tracing::debug, prelude::*};
 use std::{
     cell::RefCell,
@@ -9,6 +13,11 @@ use wasm_bindgen::prelude::*;
 
 pub struct WorldRenderer {
     world: Signal<World>,
+    display_params: Signal<DisplayParams>,
+    camera: Signal<Camera>,
+    // the camera the image was last rendered with, which fit and follow move
+    // without touching the saved camera
+    view_camera: Signal<Camera>,
     image: Rc<RefCell<Image>>,
     context: web_sys::CanvasRenderingContext2d,
     paused: Rc<AtomicBool>,
@@ -22,7 +31,9 @@ impl WorldRenderer {
     pub fn new(
         canvas: &web_sys::HtmlCanvasElement,
         mut world: Signal<World>,
-        background: Color,
+        display_params: Signal<DisplayParams>,
+        camera: Signal<Camera>,
+        mut view_camera: Signal<Camera>,
         frame_limit: Signal<usize>,
     ) -> WorldRenderer {
         let context = canvas
@@ -34,8 +45,15 @@ impl WorldRenderer {
 
         let width = canvas.width() as usize;
         let height = canvas.height() as usize;
-        let mut image = Image::new(width, height, background);
-        world.peek().render(&mut image);
+        let mut image =
+            Image::new(width, height, display_params.peek().background);
+        display_params.peek().configure_image(&mut image);
+        let mut camera_ = camera.peek().view(&view_camera.peek());
+        world.peek().render_step(&mut image, &mut camera_, 0.0);
+        image.tone_map();
+        if camera_ != *view_camera.peek() {
+            view_camera.set(camera_);
+        }
 
         let image_data = image.to_image_data();
         context.put_image_data(&image_data, 0.0, 0.0).unwrap();
@@ -73,7 +91,18 @@ impl WorldRenderer {
                 let mut world = world.write();
                 world.update();
                 let image = &mut *image.borrow_mut();
-                world.render(image);
+                let display_params = display_params.peek();
+                display_params.configure_image(image);
+                let mut camera_ = camera.peek().view(&view_camera.peek());
+                world.render_step(
+                    image,
+                    &mut camera_,
+                    display_params.trail_fade_amount(),
+                );
+                image.tone_map();
+                if camera_ != *view_camera.peek() {
+                    view_camera.set(camera_);
+                }
                 let image_data = image.to_image_data();
                 context.put_image_data(&image_data, 0.0, 0.0).unwrap();
                 frame_idx.fetch_add(1, atomic::Ordering::SeqCst);
@@ -97,6 +126,9 @@ impl WorldRenderer {
 
         WorldRenderer {
             world,
+            display_params,
+            camera,
+            view_camera,
             image,
             context,
             paused,
@@ -153,6 +185,7 @@ impl WorldRenderer {
 
         let image = &mut *self.image.borrow_mut();
         image.resize(width, height);
+        image.tone_map();
 
         let image_data = image.to_image_data();
         context.put_image_data(&image_data, 0.0, 0.0).unwrap();
@@ -165,8 +198,51 @@ impl WorldRenderer {
 
         self.frame_idx.store(0, atomic::Ordering::SeqCst);
 
+        self.display_params.peek().configure_image(image);
         image.clear();
-        self.world.peek().render(image);
+        let mut camera = self.camera.peek().view(&self.view_camera.peek());
+        self.world.peek().render_step(image, &mut camera, 0.0);
+        image.tone_map();
+        if camera != *self.view_camera.peek() {
+            self.view_camera.set(camera);
+        }
+
+        let image_data = image.to_image_data();
+        self.context.put_image_data(&image_data, 0.0, 0.0).unwrap();
+    }
+
+    // renders everything in the history again if the camera was changed
+    // since the image was last rendered, once the history no longer goes back
+    // to the start the trails already drawn are kept and only new ones are
+    // drawn with the new camera
+    pub fn redraw_camera(&mut self) {
+        let camera = self.camera.peek().view(&self.view_camera.peek());
+        if camera == *self.view_camera.peek() {
+            return;
+        }
+        if !self.world.peek().has_full_history() {
+            self.view_camera.set(camera);
+            return;
+        }
+        self.redraw();
+    }
+
+    // renders everything in the history again, so the trails match new
+    // display params
+    pub fn redraw(&mut self) {
+        let camera = self.camera.peek().view(&self.view_camera.peek());
+        let image = &mut *self.image.borrow_mut();
+        let display_params = self.display_params.peek();
+        display_params.configure_image(image);
+        self.world.peek().render_history(
+            image,
+            &camera,
+            display_params.trail_fade_amount(),
+        );
+        image.tone_map();
+        if camera != *self.view_camera.peek() {
+            self.view_camera.set(camera);
+        }
 
         let image_data = image.to_image_data();
         self.context.put_image_data(&image_data, 0.0, 0.0).unwrap();
@@ -1,26 +1,124 @@
 use crate::{
-    color::Color,
-    image::Image,
-    math::{lerp, spread_range, Vec2},
+    accumulator::{ToneMap, ToneMapping},
+    background::Background,
+    camera::Camera,
+    color::{BlendMode, BlendSpace, Color, ColorSpace},
+    density::DensityMap,
+    dither::Dither,
+    graph::{validate_partners, PartnerGraph},
+    image::{Brush, BrushFalloff, Image, Render, RenderMode, TrailStyle},
+    math::{lerp, spread_range, spread_range_within, Vec2},
+    palette::{Palette, PaletteMode},
 };
 use anyhow::{ensure, Result};
 use dioxus::logger::tracing::info;
 use rand::prelude::*;
 use rand_chacha::ChaCha20Rng;
 use serde::{Deserialize, Serialize};
-use std::{collections::VecDeque, f32::consts::PI, fmt, ops::Range};
+use std::{
+    collections::VecDeque,
+    f32::consts::PI,
+    fmt,
+    mem,
+    ops::{Range, RangeInclusive},
+};
+
+// in percent, particles are never drawn fully dark or transparent
+pub const MIN_PARTICLE_COLOR_VALUE_MID: f32 = 1.0;
+pub const MAX_PARTICLE_COLOR_VALUE_MID: f32 = 100.0;
+pub const MIN_PARTICLE_COLOR_ALPHA_MID: f32 = 1.0;
+pub const MAX_PARTICLE_COLOR_ALPHA_MID: f32 = 100.0;
+
+// frames until the age color mode is halfway through the ranges
+const AGE_HALF_LIFE: f32 = 600.0;
 
 // enough for a minute of 1000 particles
 const HISTORY_MEMORY_CAP: usize = 3600 * 1000 * size_of::<Vec2>();
 
+#[derive(Clone)]
 pub struct World {
     idxs: Range<usize>,
     positions: Vec<Vec2>,
     velocities: Vec<Vec2>,
     partners: Vec<[usize; 2]>,
     colors: Vec<Color>,
+    color_picks: Vec<ColorPick>,
+    shades: Vec<Shade>,
     history: VecDeque<Vec<Vec2>>,
-    acc_limit: i32,
+    // with the changes made during the run
+    sim_params: SimParams,
+    coloring: Coloring,
+    // seeds for the color picks and shades
+    color_seeds: [u64; 2],
+    // frames simulated so far
+    age: usize,
+}
+
+// how the particles are colored when drawn
+#[derive(Clone)]
+struct Coloring {
+    mode: ColorMode,
+    distribution: ColorDistribution,
+    hue: RangeInclusive<f32>,
+    saturation: RangeInclusive<f32>,
+    value: RangeInclusive<f32>,
+    alpha: RangeInclusive<f32>,
+    palette: Palette,
+    space: ColorSpace,
+    // degrees of hue, or of a trip along the palette and back, per frame and
+    // across the particles
+    cycle_speed: f32,
+    cycle_phase: f32,
+}
+
+// where a particle's color is, the range keeps the hue and saturation so the
+// hue can cycle all the way around
+#[derive(Debug, Clone, Copy)]
+enum ColorPick {
+    Range { hue: f32, saturation: f32 },
+    Along(f32),
+}
+
+// a particle's own brightness and opacity, which apply in every color mode
+#[derive(Debug, Clone, Copy)]
+struct Shade {
+    value: f32,
+    alpha: f32,
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize,
+)]
+pub enum ColorMode {
+    // fixed random colors picked in the ranges
+    #[default]
+    Random,
+    // the rest map a property of each particle's motion in every frame to
+    // 0..1 and through the ranges
+    Speed,
+    Heading,
+    Acceleration,
+    CentroidDistance,
+    Age,
+}
+
+// how the fixed random colors are spread over the ranges, the ones other than
+// random place each particle by more than chance so few particles still
+// cover the ranges evenly
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize,
+)]
+pub enum ColorDistribution {
+    #[default]
+    Random,
+    // low discrepancy sequence, each particle lands in the biggest gap left
+    // by the ones before
+    Stratified,
+    // the hue follows the starting position around the ring
+    RingAngle,
+    // each strongly connected component of the partner graph gets its own
+    // hue
+    Component,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,6 +126,28 @@ pub struct SimParams {
     pub seed: Seed,
     pub particle_count: usize,
     pub acc_limit: i32,
+    #[serde(default)]
+    pub partners: Option<Vec<[usize; 2]>>,
+    #[serde(default)]
+    pub speed_limit: i32,
+    // changes made while the run continued, in the order they were made
+    #[serde(default)]
+    pub events: Vec<SimEvent>,
+}
+
+// a change made partway through a run, which takes effect from the update
+// after the given frame, so the run can be replayed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SimEvent {
+    pub frame: usize,
+    pub change: SimChange,
+}
+
+// the limits are powers of 2 like in the sim params
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SimChange {
+    AccLimit(i32),
+    SpeedLimit(i32),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,8 +156,159 @@ pub struct DisplayParams {
     pub particle_color_hue_spread: f32,
     pub particle_color_saturation_mid: f32,
     pub particle_color_saturation_spread: f32,
-    pub particle_color_value: f32,
-    pub particle_color_alpha: f32,
+    pub particle_color_value_mid: f32,
+    pub particle_color_alpha_mid: f32,
+    #[serde(default)]
+    pub trail_fade: f32,
+    #[serde(default)]
+    pub blend_mode: BlendMode,
+    #[serde(default)]
+    pub render_mode: RenderMode,
+    #[serde(default)]
+    pub tone_mapping: ToneMapping,
+    #[serde(default)]
+    pub exposure: f32,
+    #[serde(default = "default_gamma")]
+    pub gamma: f32,
+    #[serde(default = "default_vibrancy")]
+    pub vibrancy: f32,
+    #[serde(default)]
+    pub trail_style: TrailStyle,
+    #[serde(default)]
+    pub brush_radius: f32,
+    #[serde(default)]
+    pub brush_falloff: BrushFalloff,
+    #[serde(default)]
+    pub brush_speed_size: f32,
+    #[serde(default)]
+    pub background: Background,
+    #[serde(default)]
+    pub color_mode: ColorMode,
+    #[serde(default)]
+    pub palette: Palette,
+    #[serde(default)]
+    pub color_space: ColorSpace,
+    #[serde(default)]
+    pub blend_space: BlendSpace,
+    #[serde(default)]
+    pub color_cycle_speed: f32,
+    #[serde(default)]
+    pub color_cycle_phase: f32,
+    #[serde(default)]
+    pub dither: Dither,
+    #[serde(default)]
+    pub color_distribution: ColorDistribution,
+    #[serde(default)]
+    pub particle_color_value_spread: f32,
+    #[serde(default)]
+    pub particle_color_alpha_spread: f32,
+}
+
+impl Default for DisplayParams {
+    fn default() -> Self {
+        Self {
+            particle_color_hue_mid: 120.0,
+            particle_color_hue_spread: 240.0,
+            particle_color_saturation_mid: 70.0,
+            particle_color_saturation_spread: 20.0,
+            particle_color_value_mid: 100.0,
+            particle_color_alpha_mid: 6.0,
+            trail_fade: 0.0,
+            blend_mode: BlendMode::Normal,
+            render_mode: RenderMode::Direct,
+            tone_mapping: ToneMapping::Linear,
+            exposure: 0.0,
+            gamma: default_gamma(),
+            vibrancy: default_vibrancy(),
+            trail_style: TrailStyle::Dots,
+            brush_radius: 0.0,
+            brush_falloff: BrushFalloff::Gaussian,
+            brush_speed_size: 0.0,
+            background: Background::default(),
+            color_mode: ColorMode::Random,
+            palette: Palette::default(),
+            color_space: ColorSpace::Hsv,
+            blend_space: BlendSpace::Srgb,
+            color_cycle_speed: 0.0,
+            color_cycle_phase: 0.0,
+            dither: Dither::None,
+            color_distribution: ColorDistribution::Random,
+            particle_color_value_spread: 0.0,
+            particle_color_alpha_spread: 0.0,
+        }
+    }
+}
+
+fn default_gamma() -> f32 {
+    1.0
+}
+
+fn default_vibrancy() -> f32 {
+    100.0
+}
+
+impl DisplayParams {
+    // fraction of the way each pixel fades toward the background per frame,
+    // zero keeps trails forever
+    pub fn trail_fade_amount(&self) -> f32 {
+        self.trail_fade / 100.0
+    }
+
+    pub fn configure_image(&self, image: &mut Image) {
+        let Self {
+            particle_color_hue_mid: _,
+            particle_color_hue_spread: _,
+            particle_color_saturation_mid: _,
+            particle_color_saturation_spread: _,
+            particle_color_value_mid: _,
+            particle_color_alpha_mid: _,
+            trail_fade: _,
+            blend_mode,
+            render_mode,
+            tone_mapping,
+            exposure,
+            gamma,
+            vibrancy,
+            trail_style,
+            brush_radius,
+            brush_falloff,
+            brush_speed_size,
+            background,
+            color_mode: _,
+            palette: _,
+            color_space: _,
+            blend_space,
+            color_cycle_speed: _,
+            color_cycle_phase: _,
+            dither,
+            color_distribution: _,
+            particle_color_value_spread: _,
+            particle_color_alpha_spread: _,
+        } = self;
+        image.set_dither(*dither);
+        image.set_background(*background);
+        image.set_blend_mode(*blend_mode);
+        image.set_blend_space(*blend_space);
+        image.set_trail_style(*trail_style);
+        image.set_brush(Brush {
+            radius: *brush_radius,
+            falloff: *brush_falloff,
+            speed_size: *brush_speed_size,
+        });
+        image.set_render(match render_mode {
+            RenderMode::Direct => Render::Direct,
+            RenderMode::Accumulate => Render::Accumulate(ToneMap {
+                tone_mapping: *tone_mapping,
+                exposure: *exposure,
+                gamma: *gamma,
+            }),
+            RenderMode::Density => Render::Density(DensityMap {
+                exposure: *exposure,
+                gamma: *gamma,
+                vibrancy: *vibrancy / 100.0,
+            }),
+        });
+    }
 }
 
 impl World {
@@ -49,16 +320,20 @@ impl World {
             seed,
             particle_count,
             acc_limit,
+            partners,
+            speed_limit: _,
+            events: _,
         } = sim_params;
-        let DisplayParams {
-            particle_color_hue_mid,
-            particle_color_hue_spread,
-            particle_color_saturation_mid,
-            particle_color_saturation_spread,
-            particle_color_value,
-            particle_color_alpha,
-        } = display_params;
         ensure!(*particle_count >= 3);
+        if let Some(partners) = partners {
+            ensure!(
+                partners.len() == *particle_count,
+                "imported partner graph has {} particles, expected \
+                 {particle_count}",
+                partners.len()
+            );
+            validate_partners(partners)?;
+        }
         info!(
             "world init - {}:{particle_count}:2^{acc_limit}",
             seed.fmt_hash()
@@ -98,66 +373,118 @@ impl World {
             .map(|_idx| Vec2::new(0.0, 0.0))
             .collect::<Vec<_>>());
 
-        let partners = with_rng!(|rng| idxs
-            .clone()
-            .map(|idx| {
-                let i = idx;
-                let mut j = rng.gen_range(idxs.clone());
-                while j == i {
-                    j = rng.gen_range(idxs.clone());
-                }
-                let mut k = rng.gen_range(idxs.clone());
-                while k == i || k == j {
-                    k = rng.gen_range(idxs.clone());
-                }
-                [j, k]
-            })
-            .collect::<Vec<_>>());
+        // the rng is still created for imported partners so the colors stay
+        // the same as for seeded partners
+        let partners =
+            with_rng!(|rng| partners.clone().unwrap_or_else(|| idxs
+                .clone()
+                .map(|idx| {
+                    let i = idx;
+                    let mut j = rng.gen_range(idxs.clone());
+                    while j == i {
+                        j = rng.gen_range(idxs.clone());
+                    }
+                    let mut k = rng.gen_range(idxs.clone());
+                    while k == i || k == j {
+                        k = rng.gen_range(idxs.clone());
+                    }
+                    [j, k]
+                })
+                .collect::<Vec<_>>()));
 
-        let colors = with_rng!(|rng| idxs
-            .clone()
-            .map(|_idx| {
-                Color::hsva(
-                    rng.gen_range(spread_range(
-                        *particle_color_hue_mid,
-                        *particle_color_hue_spread,
-                    )),
-                    rng.gen_range(spread_range(
-                        *particle_color_saturation_mid,
-                        *particle_color_saturation_spread,
-                    )),
-                    *particle_color_value,
-                    *particle_color_alpha,
-                )
-            })
-            .collect::<Vec<_>>());
+        // the colors get streams of their own so they can be picked again for
+        // new display params without touching the simulation
+        let color_seeds = [seeds.next().unwrap(), seeds.next().unwrap()];
 
         let mut history = VecDeque::new();
         history.push_back(positions.clone());
 
-        Ok(Self {
+        let mut world = Self {
             idxs,
             positions,
             velocities,
             partners,
-            colors,
+            colors: Vec::new(),
+            color_picks: Vec::new(),
+            shades: Vec::new(),
             history,
-            acc_limit: *acc_limit,
-        })
+            sim_params: sim_params.clone(),
+            coloring: Coloring::new(display_params),
+            color_seeds,
+            age: 0,
+        };
+        world.pick_colors();
+        Ok(world)
+    }
+
+    // picks the colors again for new display params, the particles carry on
+    // where they are
+    pub fn recolor(&mut self, display_params: &DisplayParams) {
+        self.coloring = Coloring::new(display_params);
+        self.pick_colors();
+    }
+
+    fn pick_colors(&mut self) {
+        let Self {
+            idxs,
+            positions: _,
+            velocities: _,
+            partners,
+            colors,
+            color_picks,
+            shades,
+            history: _,
+            sim_params: _,
+            coloring,
+            color_seeds: [picks_seed, shades_seed],
+            age: _,
+        } = self;
+
+        let mut rng = ChaCha20Rng::seed_from_u64(*picks_seed);
+        *color_picks = if let Some(spread) =
+            coloring.distribution.spread(partners, &mut rng)
+        {
+            spread
+                .into_iter()
+                .map(|(u, v)| coloring.pick_in_ranges(u, v))
+                .collect()
+        } else {
+            idxs.clone()
+                .map(|_idx| coloring.random_pick(&mut rng))
+                .collect()
+        };
+        // drawn separately so changing their ranges leaves the hues alone
+        let mut rng = ChaCha20Rng::seed_from_u64(*shades_seed);
+        *shades = idxs
+            .clone()
+            .map(|_idx| coloring.random_shade(&mut rng))
+            .collect();
+        *colors = color_picks
+            .iter()
+            .zip(shades.iter())
+            .map(|(pick, shade)| coloring.color(*pick, *shade, 0.0))
+            .collect();
     }
 
     pub fn update(&mut self) {
+        let (acc_limit, speed_limit) = self.limits(self.age + 1);
         let Self {
             idxs,
             positions,
             velocities,
             partners,
             colors: _,
+            color_picks: _,
+            shades: _,
             history,
-            acc_limit,
+            sim_params: _,
+            coloring: _,
+            color_seeds: _,
+            age,
         } = self;
 
-        let acc_limit = (*acc_limit as f32).exp2();
+        let acc_limit = (acc_limit as f32).exp2();
+        let speed_limit = (speed_limit as f32).exp2();
 
         for idx in idxs.clone() {
             let pos = positions[idx];
@@ -177,7 +504,7 @@ impl World {
             let acc = target_pos - pos;
             let acc = acc.clamp_length_max(acc_limit);
             *vel += acc;
-            *vel = vel.clamp_length_max(1.0);
+            *vel = vel.clamp_length_max(speed_limit);
         }
 
         for idx in idxs.clone() {
@@ -191,31 +518,306 @@ impl World {
             history.pop_front();
         }
         history.push_back(positions.clone());
+        *age += 1;
+    }
+
+    // the acceleration and speed limits for the update into a frame, as
+    // powers of 2
+    fn limits(&self, frame: usize) -> (i32, i32) {
+        let SimParams {
+            seed: _,
+            particle_count: _,
+            acc_limit,
+            partners: _,
+            speed_limit,
+            events,
+        } = &self.sim_params;
+        events.iter().filter(|event| event.frame < frame).fold(
+            (*acc_limit, *speed_limit),
+            |(acc_limit, speed_limit), event| match event.change {
+                SimChange::AccLimit(acc_limit) => (acc_limit, speed_limit),
+                SimChange::SpeedLimit(speed_limit) => (acc_limit, speed_limit),
+            },
+        )
+    }
+
+    // frames simulated so far
+    pub fn age(&self) -> usize {
+        self.age
+    }
+
+    // the limits the next update will use
+    pub fn acc_limit(&self) -> i32 {
+        self.limits(self.age + 1).0
+    }
+
+    pub fn speed_limit(&self) -> i32 {
+        self.limits(self.age + 1).1
+    }
+
+    // takes on events added or replaced in the sim params since the world was
+    // created, as long as nothing else changed and none of them reach back
+    // before the current frame, and returns whether it did, otherwise the
+    // world has to be created again
+    pub fn add_events(&mut self, sim_params: &SimParams) -> bool {
+        let SimParams {
+            seed,
+            particle_count,
+            acc_limit,
+            partners,
+            speed_limit,
+            events,
+        } = sim_params;
+        let current = &self.sim_params;
+        // events from the current frame on haven't been used yet
+        let applied = current
+            .events
+            .iter()
+            .take_while(|event| event.frame < self.age)
+            .count();
+        let continues = *seed == current.seed
+            && *particle_count == current.particle_count
+            && *acc_limit == current.acc_limit
+            && *partners == current.partners
+            && *speed_limit == current.speed_limit
+            && events.starts_with(&current.events[..applied])
+            && events[applied..]
+                .iter()
+                .all(|event| event.frame >= self.age);
+        if continues {
+            self.sim_params.events.clone_from(events);
+        }
+        continues
     }
 
-    pub fn render(&self, image: &mut Image) {
+    pub fn render(&self, image: &mut Image, camera: &Camera) {
+        let Self {
+            idxs: _,
+            positions: _,
+            velocities: _,
+            partners: _,
+            colors: _,
+            color_picks: _,
+            shades: _,
+            history,
+            sim_params: _,
+            coloring: _,
+            color_seeds: _,
+            age: _,
+        } = self;
+
+        // the newest history entry is the current positions
+        self.render_frame(image, camera, history.len() - 1);
+    }
+
+    // renders the newest frame over the previous ones, or all of them again if
+    // the camera had to move to keep tracking the particles, and returns
+    // whether it did
+    pub fn render_step(
+        &self,
+        image: &mut Image,
+        camera: &mut Camera,
+        trail_fade_amount: f32,
+    ) -> bool {
+        let moved = camera.track(self, image.width(), image.height());
+        if moved && self.has_full_history() {
+            self.render_history(image, camera, trail_fade_amount);
+        } else {
+            // rendering the history again would lose the older trails, so
+            // they're left where they were drawn
+            image.fade(trail_fade_amount);
+            self.render(image, camera);
+        }
+        moved
+    }
+
+    // whether every frame since the start is still in the history
+    pub fn has_full_history(&self) -> bool {
+        self.history.len() == self.age + 1
+    }
+
+    // renders every frame still in the history from scratch, for when the
+    // camera has moved
+    pub fn render_history(
+        &self,
+        image: &mut Image,
+        camera: &Camera,
+        trail_fade_amount: f32,
+    ) {
+        image.clear();
+        self.render_history_frames(
+            image,
+            camera,
+            trail_fade_amount,
+            0..self.history.len(),
+        );
+    }
+
+    // renders some of the frames in the history over what's already drawn, so
+    // a long history can be rendered a few frames at a time
+    pub fn render_history_frames(
+        &self,
+        image: &mut Image,
+        camera: &Camera,
+        trail_fade_amount: f32,
+        frame_idxs: Range<usize>,
+    ) {
+        for frame_idx in frame_idxs {
+            if frame_idx > 0 {
+                image.fade(trail_fade_amount);
+            }
+            self.render_frame(image, camera, frame_idx);
+        }
+    }
+
+    fn render_frame(
+        &self,
+        image: &mut Image,
+        camera: &Camera,
+        frame_idx: usize,
+    ) {
         let Self {
             idxs,
-            positions,
+            positions: _,
             velocities: _,
             partners: _,
             colors,
-            history: _,
-            acc_limit: _,
+            color_picks: _,
+            shades: _,
+            history,
+            sim_params: _,
+            coloring: _,
+            color_seeds: _,
+            age: _,
         } = self;
 
-        let hw = (image.width() as f32) / 2.0;
-        let hh = (image.height() as f32) / 2.0;
+        let frame_colors = self.frame_colors(frame_idx);
+        let colors = frame_colors.as_deref().unwrap_or(colors);
+        let transform = camera.transform(image.width(), image.height());
+        let positions = &history[frame_idx];
+        let prev_positions =
+            frame_idx.checked_sub(1).map(|prev_idx| &history[prev_idx]);
         for idx in idxs.clone() {
-            let pos = positions[idx];
-            let x = pos.x + hw;
-            let y = pos.y + hh;
+            let pos = transform.apply(positions[idx]);
+            let prev = prev_positions
+                .map(|prev_positions| transform.apply(prev_positions[idx]));
             let color = colors[idx];
-            image.draw_particle(x, y, color);
+            image.draw_trail(prev, pos, color);
         }
     }
 
-    pub fn generate_svg(&self, background_color: Color) -> String {
+    // the colors for a frame in the history, unless they are fixed
+    fn frame_colors(&self, frame_idx: usize) -> Option<Vec<Color>> {
+        let Self {
+            idxs,
+            positions: _,
+            velocities: _,
+            partners: _,
+            colors: _,
+            color_picks,
+            shades,
+            history,
+            sim_params: _,
+            coloring,
+            color_seeds: _,
+            age,
+        } = self;
+        let mode = coloring.mode;
+        // frames since the start, which doesn't change as the history is
+        // trimmed
+        let frame = age + 1 - (history.len() - frame_idx);
+        let (acc_limit, speed_limit) = self.limits(frame);
+        let speed_limit = (speed_limit as f32).exp2();
+        let cycle = |idx: usize| coloring.cycle(frame, idx, idxs.len());
+
+        // how far each particle moved into the frame
+        let velocity = |frame_idx: usize, idx: usize| {
+            frame_idx
+                .checked_sub(1)
+                .map_or(Vec2::new(0.0, 0.0), |prev_idx| {
+                    history[frame_idx][idx] - history[prev_idx][idx]
+                })
+        };
+        let ts = match mode {
+            ColorMode::Random if coloring.cycles() => {
+                return Some(
+                    idxs.clone()
+                        .map(|idx| {
+                            coloring.color(
+                                color_picks[idx],
+                                shades[idx],
+                                cycle(idx),
+                            )
+                        })
+                        .collect(),
+                );
+            },
+            ColorMode::Random => return None,
+            // velocities are capped at 2^speed_limit for the update into this
+            // frame, which live changes can move during the run
+            ColorMode::Speed => idxs
+                .clone()
+                .map(|idx| {
+                    velocity(frame_idx, idx).length_squared().sqrt()
+                        / speed_limit
+                })
+                .collect::<Vec<_>>(),
+            ColorMode::Heading => idxs
+                .clone()
+                .map(|idx| {
+                    let Vec2 { x, y } = velocity(frame_idx, idx);
+                    (y.atan2(x) / (2.0 * PI)).rem_euclid(1.0)
+                })
+                .collect(),
+            ColorMode::Acceleration => {
+                // the acceleration can't change the velocity by more than
+                // reversing it
+                let max = (acc_limit as f32).exp2().min(2.0 * speed_limit);
+                idxs.clone()
+                    .map(|idx| {
+                        let acc = if frame_idx >= 2 {
+                            velocity(frame_idx, idx)
+                                - velocity(frame_idx - 1, idx)
+                        } else {
+                            Vec2::new(0.0, 0.0)
+                        };
+                        acc.length_squared().sqrt() / max
+                    })
+                    .collect()
+            },
+            ColorMode::CentroidDistance => {
+                let positions = &history[frame_idx];
+                let centroid = positions
+                    .iter()
+                    .fold(Vec2::new(0.0, 0.0), |sum, &pos| sum + pos)
+                    * (1.0 / positions.len() as f32);
+                let distances = positions
+                    .iter()
+                    .map(|pos| pos.distance_squared(centroid).sqrt())
+                    .collect::<Vec<_>>();
+                let max = distances.iter().copied().fold(0.0, f32::max);
+                if max > 0.0 {
+                    distances.into_iter().map(|d| d / max).collect()
+                } else {
+                    distances
+                }
+            },
+            ColorMode::Age => {
+                let frame_age = frame as f32;
+                vec![frame_age / (frame_age + AGE_HALF_LIFE); idxs.len()]
+            },
+        };
+        Some(
+            ts.into_iter()
+                .enumerate()
+                .map(|(idx, t)| {
+                    coloring.color(coloring.pick_at(t), shades[idx], cycle(idx))
+                })
+                .collect(),
+        )
+    }
+
+    pub fn generate_svg(&self, background: &Background) -> String {
         use std::fmt::Write;
 
         let Self {
@@ -224,10 +826,21 @@ impl World {
             velocities: _,
             partners: _,
             colors,
+            color_picks: _,
+            shades: _,
             history,
-            acc_limit: _,
+            sim_params: _,
+            coloring: _,
+            color_seeds: _,
+            age: _,
         } = self;
 
+        // when the colors change over time each segment gets the color of
+        // the frame it ends in, like when rendered
+        let frame_colors = (0..history.len())
+            .map(|frame_idx| self.frame_colors(frame_idx))
+            .collect::<Option<Vec<_>>>();
+
         let mut s = String::new();
 
         macro_rules! w {
@@ -252,35 +865,440 @@ impl World {
             .unwrap_or((0.0, 0.0, 0.0, 0.0));
         let w = x1 - x;
         let h = y1 - y;
-        let bg = background_color.fmt_hex();
+        let bg = background.css();
         wln!(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
         w!(r#"<svg"#);
         w!(r#" xmlns="http://www.w3.org/2000/svg""#);
         w!(r#" width="{w}""#);
         w!(r#" height="{h}""#);
         w!(r#" viewBox="{x} {y} {w} {h}""#);
-        w!(r#" style="background: #{bg};""#);
+        w!(r#" style="background: {bg};""#);
         wln!(r#">"#);
-        for idx in idxs.clone() {
-            let color = colors[idx].fmt_hex();
+        let mut path = |color: Color, steps: &mut dyn Iterator<Item = Vec2>| {
+            let color = color.fmt_hex();
             w!(r#"  <path"#);
             w!(r#" fill="none""#);
             w!(r##" stroke="#{color}""##);
             w!(r#" stroke-linejoin="round""#);
             w!(r#" d=""#);
             let mut cmd = 'M';
-            for step in history {
-                let Vec2 { x, y } = step[idx];
+            for Vec2 { x, y } in steps {
                 w!(r#" {cmd} {x} {y}"#);
                 cmd = 'L';
             }
             w!(r#"""#);
             wln!(r#" />"#);
+        };
+        for idx in idxs.clone() {
+            let frame_colors = match &frame_colors {
+                Some(frame_colors) if history.len() > 1 => frame_colors,
+                Some(frame_colors) => {
+                    path(
+                        frame_colors[0][idx],
+                        &mut history.iter().map(|step| step[idx]),
+                    );
+                    continue;
+                },
+                None => {
+                    path(
+                        colors[idx],
+                        &mut history.iter().map(|step| step[idx]),
+                    );
+                    continue;
+                },
+            };
+            // runs of segments with the same color share a path
+            let mut run_start = 0;
+            for frame_idx in 1..history.len() {
+                let color = frame_colors[frame_idx][idx];
+                if frame_colors
+                    .get(frame_idx + 1)
+                    .is_some_and(|next| next[idx] == color)
+                {
+                    continue;
+                }
+                path(
+                    color,
+                    &mut history
+                        .range(run_start..=frame_idx)
+                        .map(|step| step[idx]),
+                );
+                run_start = frame_idx;
+            }
         }
         wln!(r#"</svg>"#);
 
         s
     }
+
+    pub fn positions(&self) -> &[Vec2] {
+        let Self {
+            idxs: _,
+            positions,
+            velocities: _,
+            partners: _,
+            colors: _,
+            color_picks: _,
+            shades: _,
+            history: _,
+            sim_params: _,
+            coloring: _,
+            color_seeds: _,
+            age: _,
+        } = self;
+
+        positions
+    }
+
+    pub fn history(&self) -> &VecDeque<Vec<Vec2>> {
+        let Self {
+            idxs: _,
+            positions: _,
+            velocities: _,
+            partners: _,
+            colors: _,
+            color_picks: _,
+            shades: _,
+            history,
+            sim_params: _,
+            coloring: _,
+            color_seeds: _,
+            age: _,
+        } = self;
+
+        history
+    }
+
+    pub fn partner_graph(&self) -> PartnerGraph<'_> {
+        let Self {
+            idxs: _,
+            positions: _,
+            velocities: _,
+            partners,
+            colors,
+            color_picks: _,
+            shades: _,
+            history: _,
+            sim_params: _,
+            coloring: _,
+            color_seeds: _,
+            age: _,
+        } = self;
+
+        PartnerGraph::new(partners, colors)
+    }
+}
+
+impl Coloring {
+    fn new(display_params: &DisplayParams) -> Self {
+        let DisplayParams {
+            particle_color_hue_mid,
+            particle_color_hue_spread,
+            particle_color_saturation_mid,
+            particle_color_saturation_spread,
+            particle_color_value_mid,
+            particle_color_alpha_mid,
+            trail_fade: _,
+            blend_mode: _,
+            render_mode: _,
+            tone_mapping: _,
+            exposure: _,
+            gamma: _,
+            vibrancy: _,
+            trail_style: _,
+            brush_radius: _,
+            brush_falloff: _,
+            brush_speed_size: _,
+            background: _,
+            color_mode,
+            palette,
+            color_space,
+            blend_space: _,
+            color_cycle_speed,
+            color_cycle_phase,
+            dither: _,
+            color_distribution,
+            particle_color_value_spread,
+            particle_color_alpha_spread,
+        } = display_params;
+        Self {
+            mode: *color_mode,
+            distribution: *color_distribution,
+            hue: spread_range(
+                *particle_color_hue_mid,
+                *particle_color_hue_spread,
+            ),
+            saturation: spread_range(
+                *particle_color_saturation_mid,
+                *particle_color_saturation_spread,
+            ),
+            // shifted to fit, clamping would pile the particles up at the
+            // ends
+            value: spread_range_within(
+                *particle_color_value_mid,
+                *particle_color_value_spread,
+                MIN_PARTICLE_COLOR_VALUE_MID..=MAX_PARTICLE_COLOR_VALUE_MID,
+            ),
+            alpha: spread_range_within(
+                *particle_color_alpha_mid,
+                *particle_color_alpha_spread,
+                MIN_PARTICLE_COLOR_ALPHA_MID..=MAX_PARTICLE_COLOR_ALPHA_MID,
+            ),
+            palette: palette.clone(),
+            space: *color_space,
+            cycle_speed: *color_cycle_speed,
+            cycle_phase: *color_cycle_phase,
+        }
+    }
+
+    fn random_pick(&self, rng: &mut impl Rng) -> ColorPick {
+        if self.palette.mode == PaletteMode::Range {
+            ColorPick::Range {
+                hue: rng.gen_range(self.hue.clone()),
+                saturation: rng.gen_range(self.saturation.clone()),
+            }
+        } else {
+            self.pick_at(rng.gen())
+        }
+    }
+
+    fn random_shade(&self, rng: &mut impl Rng) -> Shade {
+        Shade {
+            value: rng.gen_range(self.value.clone()),
+            alpha: rng.gen_range(self.alpha.clone()),
+        }
+    }
+
+    // 0..1 along the palette, or along both ranges at once
+    fn pick_at(&self, t: f32) -> ColorPick {
+        let t = t.clamp(0.0, 1.0);
+        if self.palette.mode == PaletteMode::Range {
+            self.range_pick(t, t)
+        } else {
+            ColorPick::Along(t)
+        }
+    }
+
+    // u is along the hue range or the palette, v along the saturation range
+    fn pick_in_ranges(&self, u: f32, v: f32) -> ColorPick {
+        if self.palette.mode == PaletteMode::Range {
+            self.range_pick(u, v)
+        } else {
+            ColorPick::Along(u)
+        }
+    }
+
+    fn range_pick(&self, u: f32, v: f32) -> ColorPick {
+        let hue = &self.hue;
+        let saturation = &self.saturation;
+        ColorPick::Range {
+            hue: lerp(u, 0.0, 1.0, *hue.start(), *hue.end()),
+            saturation: lerp(
+                v,
+                0.0,
+                1.0,
+                *saturation.start(),
+                *saturation.end(),
+            ),
+        }
+    }
+
+    fn cycles(&self) -> bool {
+        self.cycle_speed != 0.0 || self.cycle_phase != 0.0
+    }
+
+    // how far a particle's color has cycled by a frame, in turns
+    fn cycle(&self, frame: usize, idx: usize, count: usize) -> f32 {
+        (self.cycle_speed * frame as f32
+            + self.cycle_phase * idx as f32 / count as f32)
+            / 360.0
+    }
+
+    fn color(&self, pick: ColorPick, shade: Shade, cycle: f32) -> Color {
+        let Self {
+            mode: _,
+            distribution: _,
+            hue: _,
+            saturation: _,
+            value: _,
+            alpha: _,
+            palette,
+            space,
+            cycle_speed: _,
+            cycle_phase: _,
+        } = self;
+        match pick {
+            ColorPick::Range { hue, saturation } => space.color(
+                hue + cycle * 360.0,
+                saturation,
+                shade.value,
+                shade.alpha,
+            ),
+            ColorPick::Along(t) => {
+                // bounces back and forth along the palette, jumping from one
+                // end to the other would show as a hard edge in the trails
+                let t = if cycle != 0.0 {
+                    1.0 - (1.0 - (t + 2.0 * cycle).rem_euclid(2.0)).abs()
+                } else {
+                    t
+                };
+                if let Some(color) = palette.sample(t) {
+                    Color {
+                        a: (shade.alpha.clamp(0.0, 100.0) / 100.0 * 255.0)
+                            as u8,
+                        ..color
+                    }
+                } else {
+                    self.color(self.range_pick(t, t), shade, cycle)
+                }
+            },
+        }
+    }
+}
+
+impl ColorDistribution {
+    pub const ALL: &[Self] = &[
+        Self::Random,
+        Self::Stratified,
+        Self::RingAngle,
+        Self::Component,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Random => "random",
+            Self::Stratified => "stratified",
+            Self::RingAngle => "ring angle",
+            Self::Component => "component",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|distribution| distribution.name() == name)
+    }
+
+    // where each particle goes in 0..1 along the two ranges, none for random
+    // which draws straight from the ranges, the sequences start at offsets
+    // from the seed so every seed spreads them differently
+    pub fn spread(
+        self,
+        partners: &[[usize; 2]],
+        rng: &mut impl Rng,
+    ) -> Option<Vec<(f32, f32)>> {
+        // Roberts' R2 sequence, steps by powers of the plastic number's
+        // inverse fill the unit square evenly
+        const R2_U: f64 = 0.7548776662466927;
+        const R2_V: f64 = 0.5698402909980532;
+        // the golden ratio's inverse, the best spread in one dimension
+        const GOLDEN: f64 = 0.6180339887498949;
+
+        if self == Self::Random {
+            return None;
+        }
+        let particle_count = partners.len();
+        let offset_u = rng.gen::<f64>();
+        let offset_v = rng.gen::<f64>();
+        let at = |offset: f64, step: f64, idx: usize| {
+            (offset + step * idx as f64).fract() as f32
+        };
+        let spread = match self {
+            Self::Random => unreachable!(),
+            // the first coordinate is replaced by its rank, which spaces the
+            // particles exactly evenly along it, the sequence alone can leave
+            // gaps of more than twice the spacing
+            Self::Stratified => {
+                let mut order = (0..particle_count).collect::<Vec<_>>();
+                order.sort_by(|&i, &j| {
+                    at(offset_u, R2_U, i).total_cmp(&at(offset_u, R2_U, j))
+                });
+                let mut spread = vec![(0.0, 0.0); particle_count];
+                for (rank, &idx) in order.iter().enumerate() {
+                    spread[idx] = (
+                        ((rank as f64 + 0.5) / particle_count as f64) as f32,
+                        at(offset_v, R2_V, idx),
+                    );
+                }
+                spread
+            },
+            // particles start around the ring in index order
+            Self::RingAngle => (0..particle_count)
+                .map(|idx| {
+                    (
+                        at(offset_u, 1.0 / particle_count as f64, idx),
+                        at(offset_v, R2_V, idx),
+                    )
+                })
+                .collect(),
+            Self::Component => {
+                let (component_count, components) =
+                    PartnerGraph::new(partners, &[]).components();
+                // numbered by their first particle, so the numbering doesn't
+                // depend on how the components were found
+                let mut order = vec![None; component_count];
+                let mut next = 0;
+                for &component in &components {
+                    if order[component].is_none() {
+                        order[component] = Some(next);
+                        next += 1;
+                    }
+                }
+                components
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, &component)| {
+                        let component = order[component].unwrap_or(0);
+                        (
+                            at(offset_u, GOLDEN, component),
+                            at(offset_v, R2_V, idx),
+                        )
+                    })
+                    .collect()
+            },
+        };
+        Some(spread)
+    }
+}
+
+impl ColorMode {
+    pub const ALL: &[Self] = &[
+        Self::Random,
+        Self::Speed,
+        Self::Heading,
+        Self::Acceleration,
+        Self::CentroidDistance,
+        Self::Age,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Random => "random",
+            Self::Speed => "speed",
+            Self::Heading => "heading",
+            Self::Acceleration => "acceleration",
+            Self::CentroidDistance => "centroid distance",
+            Self::Age => "age",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|mode| mode.name() == name)
+    }
+}
+
+impl Default for SimParams {
+    fn default() -> Self {
+        Self {
+            seed: Seed::from_hash(0x27e3771584a46455),
+            particle_count: 1000,
+            acc_limit: -1,
+            partners: None,
+            speed_limit: 0,
+            events: Vec::new(),
+        }
+    }
 }
 
 impl SimParams {
@@ -289,19 +1307,67 @@ impl SimParams {
             seed,
             particle_count,
             acc_limit,
+            partners,
+            speed_limit,
+            events,
         } = self;
         let seed = seed.fmt_hash();
-        format!("{particle_count}-2_{acc_limit}-{seed}")
+        // names stay the same for runs that never changed the speed limit
+        let speed_limit = if *speed_limit == Self::default().speed_limit {
+            String::new()
+        } else {
+            format!("-s2_{speed_limit}")
+        };
+        let partners = if partners.is_some() { "-imported" } else { "" };
+        let events = if events.is_empty() {
+            String::new()
+        } else {
+            // runs with the same number of events still differ in the hash
+            let events_str = events
+                .iter()
+                .map(|SimEvent { frame, change }| match change {
+                    SimChange::AccLimit(acc_limit) => {
+                        format!("{frame}a{acc_limit}")
+                    },
+                    SimChange::SpeedLimit(speed_limit) => {
+                        format!("{frame}s{speed_limit}")
+                    },
+                })
+                .collect::<Vec<_>>()
+                .join(",");
+            let md5::Digest([b0, b1, b2, b3, ..]) = md5::compute(events_str);
+            let hash = u32::from_le_bytes([b0, b1, b2, b3]);
+            format!("-{}events_{hash:08x}", events.len())
+        };
+        format!(
+            "{particle_count}-2_{acc_limit}{speed_limit}-{seed}{partners}{events}"
+        )
+    }
+
+    // records a live change, replacing the last one if it changed the same
+    // limit in the same frame so dragging a slider doesn't pile up events
+    pub fn push_event(&mut self, event: SimEvent) {
+        if let Some(last) = self.events.last_mut() {
+            if last.frame == event.frame
+                && mem::discriminant(&last.change)
+                    == mem::discriminant(&event.change)
+            {
+                *last = event;
+                return;
+            }
+        }
+        self.events.push(event);
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Seed {
     s: String,
     n: u64,
 }
 
 impl Seed {
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(seed: String) -> Self {
         let n = hash_seed(&seed);
         Self { s: seed, n }
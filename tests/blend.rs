@@ -0,0 +1,104 @@
+// Linear light blending checked against values worked out by hand from the
+// sRGB transfer function, decoding both colors, compositing, and encoding the
+// result rounded to the nearest byte.
+
+use followers::color::{BlendMode, Color};
+
+fn blend_linear(bottom: u32, top: u32, mode: BlendMode) -> Color {
+    Color::hex(bottom).blend_mode_linear(Color::hex(top), mode)
+}
+
+#[test]
+fn normal() {
+    let cases = [
+        // half of white over black is about 73.5% in sRGB, not 50%
+        (0x000000ff, 0xffffff80, 0xbcbcbcff),
+        (0x00ff00ff, 0xff000080, 0xbcbb00ff),
+        (0xffff00ff, 0x0000ff40, 0xe0e089ff),
+        // opaque replaces, transparent leaves as is
+        (0x123456ff, 0xabcdefff, 0xabcdefff),
+        (0x123456ff, 0xabcdef00, 0x123456ff),
+        (0x00000000, 0xabcdef80, 0xabcdef80),
+        (0x00000000, 0x00000000, 0x00000000),
+    ];
+    for (bottom, top, expected) in cases {
+        assert_eq!(
+            blend_linear(bottom, top, BlendMode::Normal),
+            Color::hex(expected),
+            "{bottom:08x} under {top:08x}"
+        );
+    }
+}
+
+#[test]
+fn modes() {
+    let cases = [
+        (BlendMode::Add, 0x808080ff, 0x808080ff, 0xb0b0b0ff),
+        (BlendMode::Multiply, 0x808080ff, 0x808080ff, 0x3d3d3dff),
+        (BlendMode::Screen, 0xff0000ff, 0x0000ffff, 0xff00ffff),
+        (BlendMode::Add, 0xffffffff, 0xffffffff, 0xffffffff),
+    ];
+    for (mode, bottom, top, expected) in cases {
+        assert_eq!(
+            blend_linear(bottom, top, mode),
+            Color::hex(expected),
+            "{bottom:08x} under {top:08x} with {}",
+            mode.name()
+        );
+    }
+}
+
+// every mode with an opaque top over an opaque and a half transparent bottom,
+// where the top is only blended by how opaque the bottom is
+#[test]
+fn all_modes() {
+    let bottom = 0x66cc33;
+    let top = 0xcc6699ff;
+    let cases = [
+        (BlendMode::Normal, 0xcc6699, 0xcc6699),
+        (BlendMode::Add, 0xdfdfa0, 0xd6b09d),
+        (BlendMode::Screen, 0xd4d49e, 0xd0a99b),
+        (BlendMode::Multiply, 0x50501a, 0x9e5c71),
+        (BlendMode::Lighten, 0xcccc99, 0xcca499),
+        (BlendMode::Darken, 0x666633, 0xa36674),
+        (BlendMode::Difference, 0xb7b792, 0xc29595),
+    ];
+    assert_eq!(cases.len(), BlendMode::ALL.len());
+    for (mode, opaque, translucent) in cases {
+        for (bottom_alpha, expected) in [(0xff, opaque), (0x80, translucent)] {
+            let bottom = bottom << 8 | bottom_alpha;
+            assert_eq!(
+                blend_linear(bottom, top, mode),
+                Color::hex(expected << 8 | 0xff),
+                "{bottom:08x} under {top:08x} with {}",
+                mode.name()
+            );
+        }
+    }
+}
+
+// adding in linear light clamps after compositing, so a half transparent top
+// can still saturate, while the stored sRGB values are clamped before
+#[test]
+fn add_clamp() {
+    let bottom = Color::hex(0xeeeeeeff);
+    let top = Color::hex(0xeeeeee80);
+    assert_eq!(
+        bottom.blend_mode_linear(top, BlendMode::Add),
+        Color::hex(0xffffffff)
+    );
+    assert_eq!(
+        bottom.blend_mode(top, BlendMode::Add),
+        Color::hex(0xf6f6f6ff)
+    );
+}
+
+#[test]
+fn brighter_than_srgb() {
+    // mixing the stored values darkens overlaps of complementary colors
+    let bottom = Color::hex(0x00ff00ff);
+    let top = Color::hex(0xff000080);
+    let srgb = bottom.blend_mode(top, BlendMode::Normal);
+    let linear = bottom.blend_mode_linear(top, BlendMode::Normal);
+    assert!(linear.luminance() > srgb.luminance());
+}
@@ -0,0 +1,86 @@
+// Brush stamps, drawn opaque white on black so each pixel shows the brush's
+// weight there.
+
+use followers::{
+    background::Background,
+    color::Color,
+    image::{Brush, BrushFalloff, Image},
+};
+
+const SIZE: usize = 16;
+
+fn stamp(falloff: BrushFalloff, x: f32, y: f32, radius: f32) -> Image {
+    let mut image =
+        Image::new(SIZE, SIZE, Background::solid(Color::hex(0x000000ff)));
+    image.set_brush(Brush {
+        radius,
+        falloff,
+        speed_size: 0.0,
+    });
+    image.draw_brush(x, y, radius, Color::hex(0xffffffff));
+    image
+}
+
+// the number of fully and partly covered pixels
+fn coverage(image: &Image) -> (usize, usize) {
+    let full = image.pixels().iter().filter(|p| p.r == 255).count();
+    let partial = image
+        .pixels()
+        .iter()
+        .filter(|p| p.r > 0 && p.r < 255)
+        .count();
+    (full, partial)
+}
+
+// the sum of the weights
+fn energy(image: &Image) -> f32 {
+    image.pixels().iter().map(|p| p.r as f32 / 255.0).sum()
+}
+
+#[test]
+fn disc() {
+    // the center, its 8 neighbors, then 4 pixels half covered at a distance
+    // of 2 and 8 a little covered at a distance of sqrt(5)
+    let image = stamp(BrushFalloff::Disc, 8.5, 8.5, 2.0);
+    assert_eq!(coverage(&image), (9, 12));
+    let pixel = |x: usize, y: usize| image.pixels()[x + y * SIZE].r;
+    assert_eq!(pixel(10, 8), 127);
+    assert_eq!(pixel(10, 9), 67);
+}
+
+#[test]
+fn square() {
+    // 3 by 3 covered
+    let image = stamp(BrushFalloff::Square, 8.5, 8.5, 1.5);
+    assert_eq!(coverage(&image), (9, 0));
+    // a little smaller, only the center is fully covered, with the edges
+    // covered 3/4 of the way
+    let image = stamp(BrushFalloff::Square, 8.5, 8.5, 1.25);
+    assert_eq!(coverage(&image), (1, 8));
+    let pixel = |x: usize, y: usize| image.pixels()[x + y * SIZE].r;
+    assert_eq!(pixel(9, 8), 191);
+    assert_eq!(pixel(9, 9), 143);
+}
+
+#[test]
+fn big_brush_at_the_edge() {
+    for &falloff in BrushFalloff::ALL {
+        for (x, y) in [(0.0, 0.0), (-40.0, 8.0), (16.0, 16.0), (70.0, 70.0)] {
+            let image = stamp(falloff, x, y, 50.0);
+            assert_eq!(image.pixels().len(), SIZE * SIZE);
+        }
+    }
+}
+
+#[test]
+fn small_gaussian_like_a_dot() {
+    let mut dot =
+        Image::new(SIZE, SIZE, Background::solid(Color::hex(0x000000ff)));
+    dot.draw_particle(8.5, 8.5, Color::hex(0xffffffff));
+    assert!((energy(&dot) - 1.0).abs() < 0.01);
+    for (x, y) in [(8.5, 8.5), (8.0, 8.0), (8.25, 8.75)] {
+        let image = stamp(BrushFalloff::Gaussian, x, y, 0.5);
+        let energy = energy(&image);
+        assert!((energy - 1.0).abs() < 0.1, "({x}, {y}): {energy}");
+    }
+}
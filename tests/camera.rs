@@ -0,0 +1,37 @@
+// The fit camera only moves, and has the history rendered again, when the
+// trails no longer fit the view.
+
+use followers::{
+    background::Background,
+    camera::{Camera, CameraMode},
+    image::Image,
+    world::{DisplayParams, SimParams, World},
+};
+
+#[test]
+fn fit_moves_rarely() {
+    let display_params = DisplayParams::default();
+    let mut world = World::new(
+        &SimParams {
+            particle_count: 3,
+            acc_limit: 0,
+            ..SimParams::default()
+        },
+        &display_params,
+    )
+    .unwrap();
+    let mut image = Image::new(200, 150, Background::default());
+    let mut camera = Camera {
+        mode: CameraMode::Fit,
+        ..Camera::default()
+    };
+    let frame_count = 2000;
+    let mut move_count = 0;
+    for _ in 0..frame_count {
+        world.update();
+        if world.render_step(&mut image, &mut camera, 0.0) {
+            move_count += 1;
+        }
+    }
+    assert!(move_count < frame_count / 20, "{move_count}");
+}
@@ -0,0 +1,48 @@
+// Configs saved by older versions, which are shared as URLs and so have to
+// keep decoding as new params are added.
+
+use followers::{
+    config::{decode_config_str, encode_config_str, Config},
+    world::DisplayParams,
+};
+
+// the default config with a brightness of 80 and an opacity of 12, saved
+// before they had spreads
+const SINGLE_VALUE_AND_ALPHA: &str = "m8o4ZdImgwoj81Rjc3NDUwuTRBMzE1PT8-qPy0VblqSEnmV-8f_AHQapU04fGBhOORcACaceIOG4AMQCEY4OQIIBCJb55RflJuYsc8ksSk0uWeaTmZeaWASWOWXfAFJ9goFhiUt-STFYbIV7YmlxcWZiHpg3ZWlwfk5myhQg88z_KQoKDmf-n3LaAjQzKDEvJT936lIgnZ46aRJE8Zn_BmcazvyfBDZ3isGZA2f-n_m_PCyzKDMls3jKlDPPLN2Appz5eObXmXcgxoozt87cATJcq8_MPfN_wmKP4rIlwUXpSRDHgYglfvl5qVDLzvIJTEXIQNwO9p9vYl5pYg4A";
+
+#[test]
+fn value_and_alpha_without_spread() {
+    let config = decode_config_str(SINGLE_VALUE_AND_ALPHA).unwrap();
+    let display_params = &config.display_params;
+    assert_eq!(display_params.particle_color_value_mid, 80.0);
+    assert_eq!(display_params.particle_color_value_spread, 0.0);
+    assert_eq!(display_params.particle_color_alpha_mid, 12.0);
+    assert_eq!(display_params.particle_color_alpha_spread, 0.0);
+}
+
+#[test]
+fn no_speed_limit_or_events() {
+    let config = decode_config_str(SINGLE_VALUE_AND_ALPHA).unwrap();
+    assert_eq!(config.sim_params.speed_limit, 0);
+    assert!(config.sim_params.events.is_empty());
+}
+
+#[test]
+fn value_and_alpha_spread_round_trip() {
+    let config = Config {
+        display_params: DisplayParams {
+            particle_color_value_mid: 70.0,
+            particle_color_value_spread: 40.0,
+            particle_color_alpha_mid: 10.0,
+            particle_color_alpha_spread: 8.0,
+            ..DisplayParams::default()
+        },
+        ..Config::default()
+    };
+    let decoded = decode_config_str(&encode_config_str(config)).unwrap();
+    let display_params = &decoded.display_params;
+    assert_eq!(display_params.particle_color_value_mid, 70.0);
+    assert_eq!(display_params.particle_color_value_spread, 40.0);
+    assert_eq!(display_params.particle_color_alpha_mid, 10.0);
+    assert_eq!(display_params.particle_color_alpha_spread, 8.0);
+}
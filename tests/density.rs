@@ -0,0 +1,63 @@
+// Density rendering brightens pixels by how often they're visited, and leaves
+// pixels that were never visited as the background.
+
+use followers::{
+    color::{Color, LinearColor},
+    density::{DensityMap, Histogram},
+    dither::Dither,
+};
+
+const DENSITY_MAP: DensityMap = DensityMap {
+    exposure: 0.0,
+    gamma: 2.2,
+    vibrancy: 1.0,
+};
+
+// tone maps one row of pixels, visited the given number of times in the
+// given color
+fn render(visits: &[(usize, u32)], background: Color) -> Vec<Color> {
+    let mut histogram = Histogram::new(visits.len(), 1, DENSITY_MAP);
+    for (x, &(count, color)) in visits.iter().enumerate() {
+        for _ in 0..count {
+            histogram.add(
+                x,
+                0,
+                LinearColor::from_color(Color::hex(color)),
+                1.0,
+            );
+        }
+    }
+    let background = vec![background; visits.len()];
+    let mut out = vec![Color::hex(0x00000000); visits.len()];
+    histogram.tone_map(&mut out, &background, Dither::None);
+    out
+}
+
+#[test]
+fn empty_is_background() {
+    let background = Color::hex(0x123456ff);
+    let out = render(&[(0, 0xffffffff), (5, 0xffffffff)], background);
+    assert_eq!(out[0], background);
+    assert_ne!(out[1], background);
+}
+
+#[test]
+fn more_visits_are_brighter() {
+    let counts = [1, 2, 10, 100, 1000];
+    let visits = counts.map(|count| (count, 0xffffffff));
+    let out = render(&visits, Color::hex(0x000000ff));
+    for pair in out.windows(2) {
+        assert!(pair[0].r < pair[1].r, "{:?}", out);
+    }
+    // the most visited pixel is white
+    assert_eq!(out[counts.len() - 1], Color::hex(0xffffffff));
+}
+
+#[test]
+fn translucent_visits_count_less() {
+    let out = render(
+        &[(10, 0xffffffff), (10, 0xffffff40), (1000, 0xffffffff)],
+        Color::hex(0x000000ff),
+    );
+    assert!(out[1].r < out[0].r, "{:?}", out);
+}
@@ -0,0 +1,125 @@
+// How the color distributions spread the particles along the hue range, u is
+// the position along it.
+
+use followers::{graph::PartnerGraph, world::ColorDistribution};
+use rand::prelude::*;
+use rand_chacha::ChaCha20Rng;
+
+fn spread(
+    distribution: ColorDistribution,
+    partners: &[[usize; 2]],
+    seed: u64,
+) -> Vec<f32> {
+    let mut rng = ChaCha20Rng::seed_from_u64(seed);
+    distribution
+        .spread(partners, &mut rng)
+        .unwrap()
+        .into_iter()
+        .map(|(u, _v)| u)
+        .collect()
+}
+
+// random partners, which usually leave several components
+fn random_partners(particle_count: usize, seed: u64) -> Vec<[usize; 2]> {
+    let mut rng = ChaCha20Rng::seed_from_u64(seed);
+    (0..particle_count)
+        .map(|idx| {
+            let mut pick = || loop {
+                let partner = rng.gen_range(0..particle_count);
+                if partner != idx {
+                    return partner;
+                }
+            };
+            let first = pick();
+            loop {
+                let second = pick();
+                if second != first {
+                    return [first, second];
+                }
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn random_has_no_spread() {
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    let partners = random_partners(10, 0);
+    assert!(ColorDistribution::Random
+        .spread(&partners, &mut rng)
+        .is_none());
+}
+
+#[test]
+fn component() {
+    // two rings of 5, then a chain hanging off the first ring with a cycle
+    // of its own
+    let mut partners = (0..10)
+        .map(|idx| {
+            let ring = idx / 5 * 5;
+            [ring + (idx + 1) % 5, ring + (idx + 2) % 5]
+        })
+        .collect::<Vec<_>>();
+    partners.extend([[11, 0], [12, 0], [10, 0]]);
+    let u = spread(ColorDistribution::Component, &partners, 1);
+    assert!(u[..5].iter().all(|&u_| u_ == u[0]));
+    assert!(u[5..10].iter().all(|&u_| u_ == u[5]));
+    assert!(u[10..].iter().all(|&u_| u_ == u[10]));
+    assert_ne!(u[0], u[5]);
+    assert_ne!(u[0], u[10]);
+    assert_ne!(u[5], u[10]);
+
+    for seed in 0..10 {
+        let partners = random_partners(200, seed);
+        let (_, components) = PartnerGraph::new(&partners, &[]).components();
+        let u = spread(ColorDistribution::Component, &partners, seed);
+        for i in 0..partners.len() {
+            for j in 0..partners.len() {
+                if components[i] == components[j] {
+                    assert_eq!(u[i], u[j], "seed {seed}: {i} and {j}");
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn stratified() {
+    for particle_count in [3, 10, 100, 1000] {
+        for seed in 0..5 {
+            let partners = random_partners(particle_count, seed);
+            let mut u = spread(ColorDistribution::Stratified, &partners, seed);
+            u.sort_by(f32::total_cmp);
+            // including the gap wrapping around from the last to the first
+            let max_gap = u
+                .windows(2)
+                .map(|pair| pair[1] - pair[0])
+                .chain([u[0] + 1.0 - u[particle_count - 1]])
+                .fold(0.0, f32::max);
+            assert!(
+                max_gap < 2.0 / particle_count as f32,
+                "{particle_count} particles, seed {seed}: {max_gap}"
+            );
+        }
+    }
+}
+
+#[test]
+fn ring_angle() {
+    for particle_count in [3, 10, 100, 1000] {
+        for seed in 0..5 {
+            let partners = random_partners(particle_count, seed);
+            let u = spread(ColorDistribution::RingAngle, &partners, seed);
+            let wraps = u.windows(2).filter(|pair| pair[1] < pair[0]).count();
+            assert!(wraps <= 1, "{particle_count} particles, seed {seed}");
+            // evenly spaced otherwise
+            for pair in u.windows(2) {
+                let step = (pair[1] - pair[0]).rem_euclid(1.0);
+                assert!(
+                    (step - 1.0 / particle_count as f32).abs() < 1e-4,
+                    "{particle_count} particles, seed {seed}: {step}"
+                );
+            }
+        }
+    }
+}
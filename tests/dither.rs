@@ -0,0 +1,119 @@
+// Dither patterns used when rounding to bytes.
+
+use followers::{
+    background::Background,
+    color::Color,
+    dither::Dither,
+    image::Image,
+};
+
+fn tile(dither: Dither, size: usize) -> Vec<f32> {
+    (0..size * size)
+        .map(|idx| dither.offset(idx % size, idx / size))
+        .collect()
+}
+
+// every threshold is used once per tile, evenly spaced in -0.5..0.5
+fn assert_permutation(offsets: &[f32]) {
+    let len = offsets.len() as f32;
+    let mut ranks = offsets
+        .iter()
+        .map(|offset| ((offset + 0.5) * len - 0.5).round() as usize)
+        .collect::<Vec<_>>();
+    ranks.sort_unstable();
+    assert!(ranks.iter().copied().eq(0..offsets.len()));
+}
+
+#[test]
+fn bayer() {
+    let offsets = tile(Dither::Bayer, 8);
+    assert_permutation(&offsets);
+    // the classic 2x2 corner, scaled to 8x8
+    assert_eq!(
+        [offsets[0], offsets[1], offsets[8], offsets[9]]
+            .map(|offset| { ((offset + 0.5) * 64.0 - 0.5).round() as usize }),
+        [0, 32, 48, 16]
+    );
+    assert_eq!(Dither::Bayer.offset(3, 5), Dither::Bayer.offset(11, 13));
+}
+
+#[test]
+fn blue_noise() {
+    let offsets = tile(Dither::BlueNoise, 64);
+    assert_permutation(&offsets);
+    assert_eq!(
+        Dither::BlueNoise.offset(10, 20),
+        Dither::BlueNoise.offset(74, 84)
+    );
+    // no two of the lowest thresholds are next to each other
+    let low = |x: usize, y: usize| offsets[(x % 64) + (y % 64) * 64] < -0.4;
+    for y in 0..64 {
+        for x in 0..64 {
+            if low(x, y) {
+                assert!(!low(x + 1, y) && !low(x, y + 1), "{x} {y}");
+            }
+        }
+    }
+}
+
+#[test]
+fn quantize() {
+    for dither in Dither::ALL {
+        // whole values never move
+        for value in [0.0, 1.0, 128.0, 255.0] {
+            for (x, y) in [(0, 0), (1, 0), (5, 7), (40, 3)] {
+                assert_eq!(dither.quantize(value, x, y), value as u8);
+            }
+        }
+        assert_eq!(dither.quantize(-3.0, 0, 0), 0);
+        assert_eq!(dither.quantize(300.0, 0, 0), 255);
+    }
+    assert_eq!(Dither::None.quantize(10.4, 3, 3), 10);
+    assert_eq!(Dither::None.quantize(10.6, 3, 3), 11);
+}
+
+#[test]
+fn average_is_preserved() {
+    for dither in [Dither::Bayer, Dither::BlueNoise] {
+        for value in [10.25, 0.1, 99.9, 200.5] {
+            let size = 64;
+            let sum = (0..size * size)
+                .map(|idx| {
+                    dither.quantize(value, idx % size, idx / size) as f32
+                })
+                .sum::<f32>();
+            let mean = sum / (size * size) as f32;
+            assert!((mean - value).abs() < 0.02, "{dither:?} {value} {mean}");
+        }
+    }
+}
+
+// fading trails in the direct mode are dithered too, without moving their
+// average
+#[test]
+fn direct_fade() {
+    let size = 16;
+    let faded = |dither: Dither| {
+        let mut image =
+            Image::new(size, size, Background::solid(Color::hex(0x000000ff)));
+        image.set_dither(dither);
+        for y in 0..size {
+            for x in 0..size {
+                image.put_pixel(x, y, Color::hex(0xffffffff));
+            }
+        }
+        for _ in 0..19 {
+            image.fade(0.1);
+        }
+        image.pixels().iter().map(|p| p.r).collect::<Vec<_>>()
+    };
+    // 255 * 0.9^19 is about 34.4
+    assert!(faded(Dither::None).iter().all(|&r| r == 34));
+    for dither in [Dither::Bayer, Dither::BlueNoise] {
+        let reds = faded(dither);
+        assert!(reds.iter().any(|&r| r != reds[0]), "{dither:?}");
+        let mean =
+            reds.iter().map(|&r| r as f32).sum::<f32>() / reds.len() as f32;
+        assert!((mean - 34.4).abs() < 0.1, "{dither:?} {mean}");
+    }
+}
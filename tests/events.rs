@@ -0,0 +1,177 @@
+// Limits changed partway through a run, which have to replay the same from
+// the sim params as when they were made live.
+
+use followers::{
+    background::Background,
+    world::{DisplayParams, SimChange, SimEvent, SimParams, World},
+};
+
+const PARTICLE_COUNT: usize = 50;
+
+fn sim_params(events: Vec<SimEvent>) -> SimParams {
+    SimParams {
+        particle_count: PARTICLE_COUNT,
+        events,
+        ..SimParams::default()
+    }
+}
+
+fn svg(world: &World) -> String {
+    world.generate_svg(&Background::default())
+}
+
+fn run(world: &mut World, frame_count: usize) {
+    for _ in 0..frame_count {
+        world.update();
+    }
+}
+
+#[test]
+fn live_changes_replay() {
+    let events = vec![
+        SimEvent {
+            frame: 20,
+            change: SimChange::AccLimit(-4),
+        },
+        SimEvent {
+            frame: 35,
+            change: SimChange::SpeedLimit(-2),
+        },
+        SimEvent {
+            frame: 35,
+            change: SimChange::AccLimit(1),
+        },
+    ];
+    let display_params = DisplayParams::default();
+
+    let mut live =
+        World::new(&sim_params(Vec::new()), &display_params).unwrap();
+    run(&mut live, 20);
+    assert!(live.add_events(&sim_params(events[..1].to_vec())));
+    assert_eq!(live.acc_limit(), -4);
+    run(&mut live, 15);
+    assert!(live.add_events(&sim_params(events.clone())));
+    assert_eq!((live.acc_limit(), live.speed_limit()), (1, -2));
+    run(&mut live, 25);
+
+    let mut replayed =
+        World::new(&sim_params(events), &display_params).unwrap();
+    assert_eq!((replayed.acc_limit(), replayed.speed_limit()), (-1, 0));
+    run(&mut replayed, 60);
+    assert_eq!(svg(&live), svg(&replayed));
+
+    let mut unchanged =
+        World::new(&sim_params(Vec::new()), &display_params).unwrap();
+    run(&mut unchanged, 60);
+    assert_ne!(svg(&live), svg(&unchanged));
+}
+
+#[test]
+fn speed_limit() {
+    let sim_params = SimParams {
+        speed_limit: -3,
+        ..sim_params(Vec::new())
+    };
+    let mut world = World::new(&sim_params, &DisplayParams::default()).unwrap();
+    let mut previous = world.positions().to_vec();
+    for _ in 0..30 {
+        world.update();
+        for (pos, previous) in world.positions().iter().zip(&previous) {
+            // 2^-3 per frame
+            assert!(pos.distance_squared(*previous) <= 0.125 * 0.125 + 1e-6);
+        }
+        previous = world.positions().to_vec();
+    }
+}
+
+#[test]
+fn other_changes_restart() {
+    let display_params = DisplayParams::default();
+    let event = |frame| SimEvent {
+        frame,
+        change: SimChange::SpeedLimit(1),
+    };
+    let mut world =
+        World::new(&sim_params(vec![event(5)]), &display_params).unwrap();
+    run(&mut world, 10);
+    // already past the frame the change would have been made at
+    assert!(!world.add_events(&sim_params(vec![event(5), event(8)])));
+    // dropping changes that were made
+    assert!(!world.add_events(&sim_params(Vec::new())));
+    assert!(!world.add_events(&SimParams {
+        acc_limit: 2,
+        ..sim_params(vec![event(5), event(10)])
+    }));
+    assert!(world.add_events(&sim_params(vec![event(5), event(10)])));
+}
+
+// dragging a slider keeps replacing the change made in the current frame
+#[test]
+fn same_frame_changes_replace() {
+    let display_params = DisplayParams::default();
+    let mut params = sim_params(Vec::new());
+    let mut world = World::new(&params, &display_params).unwrap();
+    run(&mut world, 10);
+    for speed_limit in [1, 2, 3] {
+        params.push_event(SimEvent {
+            frame: 10,
+            change: SimChange::SpeedLimit(speed_limit),
+        });
+        assert!(world.add_events(&params));
+    }
+    params.push_event(SimEvent {
+        frame: 10,
+        change: SimChange::AccLimit(-4),
+    });
+    assert!(world.add_events(&params));
+    assert_eq!(
+        params.events,
+        [
+            SimEvent {
+                frame: 10,
+                change: SimChange::SpeedLimit(3),
+            },
+            SimEvent {
+                frame: 10,
+                change: SimChange::AccLimit(-4),
+            },
+        ]
+    );
+    assert_eq!((world.acc_limit(), world.speed_limit()), (-4, 3));
+
+    // the replaced run matches one that only ever had the last changes
+    let mut replay = World::new(&params, &display_params).unwrap();
+    run(&mut world, 20);
+    run(&mut replay, 30);
+    assert_eq!(svg(&world), svg(&replay));
+}
+
+#[test]
+fn file_name_includes_changes() {
+    let event = |frame, speed_limit| SimEvent {
+        frame,
+        change: SimChange::SpeedLimit(speed_limit),
+    };
+    let file_names = [
+        sim_params(Vec::new()),
+        SimParams {
+            speed_limit: 2,
+            ..sim_params(Vec::new())
+        },
+        sim_params(vec![event(5, 1)]),
+        sim_params(vec![event(5, 2)]),
+        sim_params(vec![event(6, 1)]),
+        sim_params(vec![event(5, 1), event(6, 1)]),
+    ]
+    .map(|params| params.file_name());
+    for (i, a) in file_names.iter().enumerate() {
+        for b in &file_names[i + 1..] {
+            assert_ne!(a, b);
+        }
+    } // runs that never touched the speed limit or made changes keep the
+      // names they had before either existed
+    assert_eq!(
+        SimParams::default().file_name(),
+        "1000-2_-1-0x27e3771584a46455"
+    );
+}
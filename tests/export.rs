@@ -0,0 +1,84 @@
+// Exports rendered and encoded a piece at a time match doing it all at once,
+// and exports too big to fit in memory fail instead of aborting.
+
+use followers::{
+    camera::Camera,
+    export::{render_export, Export, ExportParams, ExportSource},
+    image::{Image, RenderMode},
+    world::{DisplayParams, SimParams, World},
+};
+
+fn run(sim_params: &SimParams, display_params: &DisplayParams) -> World {
+    let mut world = World::new(sim_params, display_params).unwrap();
+    for _ in 0..50 {
+        world.update();
+    }
+    world
+}
+
+fn export_params(source: ExportSource) -> ExportParams {
+    ExportParams {
+        frame_count: 50,
+        view_width: 100,
+        view_height: 60,
+        width: 200,
+        height: 120,
+        camera: Camera::default(),
+        source,
+    }
+}
+
+#[test]
+fn in_steps() {
+    let sim_params = SimParams {
+        particle_count: 100,
+        ..SimParams::default()
+    };
+    let display_params = DisplayParams {
+        trail_fade: 2.0,
+        ..DisplayParams::default()
+    };
+    let world = run(&sim_params, &display_params);
+    for &source in ExportSource::ALL {
+        let export_params = export_params(source);
+        let whole =
+            render_export(&world, &sim_params, &display_params, &export_params)
+                .unwrap();
+        let mut export =
+            Export::new(&world, &sim_params, &display_params, &export_params)
+                .unwrap();
+        while !export.step(7) {}
+        let image = export.finish();
+        assert!(image.pixels() == whole.pixels(), "{}", source.name());
+
+        let mut encoder = image.png_encoder().unwrap();
+        while !encoder.step(&image, 13).unwrap() {}
+        let png = encoder.finish().unwrap();
+        assert_eq!(png, whole.encode_png().unwrap());
+        let (width, height, pixels) = Image::decode_png(&png).unwrap();
+        assert_eq!((width, height), (200, 120));
+        assert!(pixels == whole.pixels());
+    }
+}
+
+#[test]
+fn too_big() {
+    let sim_params = SimParams::default();
+    for &render_mode in RenderMode::ALL {
+        let display_params = DisplayParams {
+            render_mode,
+            ..DisplayParams::default()
+        };
+        let world = World::new(&sim_params, &display_params).unwrap();
+        let export_params = ExportParams {
+            width: 16384,
+            height: 16384,
+            ..export_params(ExportSource::History)
+        };
+        let error =
+            Export::new(&world, &sim_params, &display_params, &export_params)
+                .err()
+                .unwrap();
+        assert!(error.to_string().contains("too big"), "{error}");
+    }
+}
@@ -0,0 +1,579 @@
+// Hashes of the simulation and rendered image for a set of reference
+// configurations, so changes that break the promise that the same seed,
+// particle count, and acceleration limit always produce the same picture are
+// caught. If a change to the output is intentional, regenerate the goldens
+// with `just bless` and commit the result.
+
+use followers::{
+    accumulator::ToneMapping,
+    background::{Background, BackgroundStyle},
+    camera::{Camera, CameraMode},
+    color::{BlendMode, BlendSpace, Color, ColorSpace},
+    dither::Dither,
+    image::{BrushFalloff, Image, RenderMode, TrailStyle},
+    palette::{ColorStop, NamedPalette, Palette, PaletteMode},
+    world::{
+        ColorDistribution,
+        ColorMode,
+        DisplayParams,
+        Seed,
+        SimChange,
+        SimEvent,
+        SimParams,
+        World,
+    },
+};
+use std::collections::BTreeMap;
+use zerocopy::IntoBytes;
+
+const GOLDEN_PATH: &str =
+    concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden.txt");
+const BLESS_ENV_VAR: &str = "FOLLOWERS_BLESS";
+
+const IMAGE_WIDTH: usize = 320;
+const IMAGE_HEIGHT: usize = 240;
+
+struct Reference {
+    name: &'static str,
+    sim_params: SimParams,
+    display_params: DisplayParams,
+    frame_count: usize,
+    camera: Camera,
+}
+
+fn references() -> Vec<Reference> {
+    vec![
+        Reference {
+            name: "default",
+            sim_params: SimParams::default(),
+            display_params: DisplayParams::default(),
+            frame_count: 300,
+            camera: Camera::default(),
+        },
+        Reference {
+            name: "minimal",
+            sim_params: SimParams {
+                seed: Seed::from_str("followers".to_owned()),
+                particle_count: 3,
+                acc_limit: 0,
+                ..SimParams::default()
+            },
+            display_params: DisplayParams::default(),
+            frame_count: 1000,
+            camera: Camera::default(),
+        },
+        Reference {
+            name: "fast",
+            sim_params: SimParams {
+                seed: Seed::from_hash(0x0123456789abcdef),
+                particle_count: 500,
+                acc_limit: 4,
+                ..SimParams::default()
+            },
+            display_params: DisplayParams::default(),
+            frame_count: 200,
+            camera: Camera::default(),
+        },
+        Reference {
+            name: "slow",
+            sim_params: SimParams {
+                seed: Seed::from_str("slow".to_owned()),
+                particle_count: 2000,
+                acc_limit: -8,
+                ..SimParams::default()
+            },
+            display_params: DisplayParams {
+                particle_color_hue_mid: 300.0,
+                particle_color_hue_spread: 60.0,
+                particle_color_saturation_mid: 50.0,
+                particle_color_saturation_spread: 100.0,
+                particle_color_value_mid: 80.0,
+                particle_color_alpha_mid: 50.0,
+                ..DisplayParams::default()
+            },
+            frame_count: 200,
+            camera: Camera::default(),
+        },
+        Reference {
+            name: "additive",
+            sim_params: SimParams::default(),
+            display_params: DisplayParams {
+                blend_mode: BlendMode::Add,
+                ..DisplayParams::default()
+            },
+            frame_count: 300,
+            camera: Camera::default(),
+        },
+        Reference {
+            name: "accumulate",
+            sim_params: SimParams::default(),
+            display_params: DisplayParams {
+                blend_mode: BlendMode::Add,
+                render_mode: RenderMode::Accumulate,
+                tone_mapping: ToneMapping::Filmic,
+                exposure: 1.0,
+                ..DisplayParams::default()
+            },
+            frame_count: 300,
+            camera: Camera::default(),
+        },
+        Reference {
+            name: "density",
+            sim_params: SimParams::default(),
+            display_params: DisplayParams {
+                render_mode: RenderMode::Density,
+                gamma: 2.0,
+                vibrancy: 50.0,
+                ..DisplayParams::default()
+            },
+            frame_count: 300,
+            camera: Camera::default(),
+        },
+        Reference {
+            name: "segments",
+            sim_params: SimParams {
+                seed: Seed::from_hash(0x0123456789abcdef),
+                particle_count: 500,
+                acc_limit: 4,
+                ..SimParams::default()
+            },
+            display_params: DisplayParams {
+                trail_style: TrailStyle::Segments,
+                ..DisplayParams::default()
+            },
+            frame_count: 200,
+            camera: Camera::default(),
+        },
+        Reference {
+            name: "brush",
+            sim_params: SimParams {
+                seed: Seed::from_hash(0x0123456789abcdef),
+                particle_count: 500,
+                acc_limit: 4,
+                ..SimParams::default()
+            },
+            display_params: DisplayParams {
+                trail_style: TrailStyle::Segments,
+                brush_radius: 1.5,
+                brush_falloff: BrushFalloff::Disc,
+                brush_speed_size: 2.0,
+                ..DisplayParams::default()
+            },
+            frame_count: 200,
+            camera: Camera::default(),
+        },
+        Reference {
+            name: "imported-partners",
+            sim_params: SimParams {
+                particle_count: 100,
+                partners: Some(
+                    (0..100).map(|i| [(i + 1) % 100, (i + 7) % 100]).collect(),
+                ),
+                ..SimParams::default()
+            },
+            display_params: DisplayParams::default(),
+            frame_count: 300,
+            camera: Camera::default(),
+        },
+        Reference {
+            name: "linear-background",
+            sim_params: SimParams::default(),
+            display_params: DisplayParams {
+                trail_fade: 5.0,
+                background: Background {
+                    style: BackgroundStyle::Linear,
+                    color: Color::hex(0x102030ff),
+                    end_color: Color::hex(0x402010ff),
+                    angle: 30.0,
+                },
+                ..DisplayParams::default()
+            },
+            frame_count: 300,
+            camera: Camera::default(),
+        },
+        Reference {
+            name: "radial-background",
+            sim_params: SimParams::default(),
+            display_params: DisplayParams {
+                render_mode: RenderMode::Density,
+                background: Background {
+                    style: BackgroundStyle::Radial,
+                    color: Color::hex(0x303030ff),
+                    end_color: Color::hex(0x000000ff),
+                    ..Background::default()
+                },
+                ..DisplayParams::default()
+            },
+            frame_count: 300,
+            camera: Camera::default(),
+        },
+        Reference {
+            name: "color-heading",
+            sim_params: SimParams::default(),
+            display_params: DisplayParams {
+                color_mode: ColorMode::Heading,
+                trail_style: TrailStyle::Segments,
+                ..DisplayParams::default()
+            },
+            frame_count: 300,
+            camera: Camera::default(),
+        },
+        Reference {
+            name: "color-centroid-distance",
+            sim_params: SimParams::default(),
+            display_params: DisplayParams {
+                color_mode: ColorMode::CentroidDistance,
+                ..DisplayParams::default()
+            },
+            frame_count: 300,
+            camera: Camera::default(),
+        },
+        Reference {
+            name: "palette-gradient",
+            sim_params: SimParams::default(),
+            display_params: DisplayParams {
+                palette: Palette {
+                    mode: PaletteMode::Gradient,
+                    stops: vec![
+                        ColorStop {
+                            position: 0.0,
+                            color: Color::hex(0xff3080ff),
+                        },
+                        ColorStop {
+                            position: 0.7,
+                            color: Color::hex(0xffe040ff),
+                        },
+                        ColorStop {
+                            position: 0.3,
+                            color: Color::hex(0x30c0ffff),
+                        },
+                    ],
+                    ..Palette::default()
+                },
+                ..DisplayParams::default()
+            },
+            frame_count: 300,
+            camera: Camera::default(),
+        },
+        Reference {
+            name: "palette-named-speed",
+            sim_params: SimParams::default(),
+            display_params: DisplayParams {
+                color_mode: ColorMode::Speed,
+                palette: Palette {
+                    mode: PaletteMode::Named,
+                    named: NamedPalette::Magma,
+                    ..Palette::default()
+                },
+                ..DisplayParams::default()
+            },
+            frame_count: 300,
+            camera: Camera::default(),
+        },
+        Reference {
+            name: "palette-swatches",
+            sim_params: SimParams::default(),
+            display_params: DisplayParams {
+                palette: Palette {
+                    mode: PaletteMode::Swatches,
+                    ..Palette::default()
+                },
+                ..DisplayParams::default()
+            },
+            frame_count: 300,
+            camera: Camera::default(),
+        },
+        Reference {
+            name: "oklch",
+            sim_params: SimParams::default(),
+            display_params: DisplayParams {
+                particle_color_saturation_mid: 60.0,
+                particle_color_saturation_spread: 80.0,
+                particle_color_value_mid: 75.0,
+                color_space: ColorSpace::Oklch,
+                ..DisplayParams::default()
+            },
+            frame_count: 300,
+            camera: Camera::default(),
+        },
+        Reference {
+            name: "linear-blend",
+            sim_params: SimParams::default(),
+            display_params: DisplayParams {
+                particle_color_alpha_mid: 20.0,
+                blend_space: BlendSpace::Linear,
+                ..DisplayParams::default()
+            },
+            frame_count: 300,
+            camera: Camera::default(),
+        },
+        Reference {
+            name: "palette-weighted-swatches",
+            sim_params: SimParams::default(),
+            display_params: DisplayParams {
+                palette: Palette {
+                    mode: PaletteMode::Swatches,
+                    swatch_weights: vec![3.0, 0.5, 0.0, 1.5],
+                    ..Palette::default()
+                },
+                ..DisplayParams::default()
+            },
+            frame_count: 300,
+            camera: Camera::default(),
+        },
+        Reference {
+            name: "color-cycle",
+            sim_params: SimParams::default(),
+            display_params: DisplayParams {
+                color_cycle_speed: 3.0,
+                color_cycle_phase: 90.0,
+                ..DisplayParams::default()
+            },
+            frame_count: 300,
+            camera: Camera::default(),
+        },
+        Reference {
+            name: "color-cycle-named-speed",
+            sim_params: SimParams::default(),
+            display_params: DisplayParams {
+                color_mode: ColorMode::Speed,
+                palette: Palette {
+                    mode: PaletteMode::Named,
+                    named: NamedPalette::Plasma,
+                    ..Palette::default()
+                },
+                color_cycle_speed: 2.0,
+                ..DisplayParams::default()
+            },
+            frame_count: 300,
+            camera: Camera::default(),
+        },
+        Reference {
+            name: "dither-bayer",
+            sim_params: SimParams::default(),
+            display_params: DisplayParams {
+                render_mode: RenderMode::Accumulate,
+                background: Background {
+                    style: BackgroundStyle::Linear,
+                    color: Color::hex(0x000000ff),
+                    end_color: Color::hex(0x101028ff),
+                    ..Background::default()
+                },
+                dither: Dither::Bayer,
+                ..DisplayParams::default()
+            },
+            frame_count: 300,
+            camera: Camera::default(),
+        },
+        Reference {
+            name: "dither-blue-noise",
+            sim_params: SimParams::default(),
+            display_params: DisplayParams {
+                render_mode: RenderMode::Density,
+                background: Background {
+                    style: BackgroundStyle::Radial,
+                    color: Color::hex(0x181020ff),
+                    end_color: Color::hex(0x000000ff),
+                    ..Background::default()
+                },
+                dither: Dither::BlueNoise,
+                ..DisplayParams::default()
+            },
+            frame_count: 300,
+            camera: Camera::default(),
+        },
+        Reference {
+            name: "color-stratified",
+            sim_params: SimParams {
+                particle_count: 200,
+                ..SimParams::default()
+            },
+            display_params: DisplayParams {
+                color_distribution: ColorDistribution::Stratified,
+                ..DisplayParams::default()
+            },
+            frame_count: 300,
+            camera: Camera::default(),
+        },
+        Reference {
+            name: "color-ring-angle",
+            sim_params: SimParams {
+                particle_count: 200,
+                ..SimParams::default()
+            },
+            display_params: DisplayParams {
+                color_distribution: ColorDistribution::RingAngle,
+                ..DisplayParams::default()
+            },
+            frame_count: 300,
+            camera: Camera::default(),
+        },
+        Reference {
+            name: "color-component",
+            sim_params: SimParams {
+                particle_count: 500,
+                ..SimParams::default()
+            },
+            display_params: DisplayParams {
+                color_distribution: ColorDistribution::Component,
+                ..DisplayParams::default()
+            },
+            frame_count: 300,
+            camera: Camera::default(),
+        },
+        Reference {
+            name: "value-alpha-spread",
+            sim_params: SimParams {
+                particle_count: 500,
+                ..SimParams::default()
+            },
+            display_params: DisplayParams {
+                particle_color_value_mid: 70.0,
+                particle_color_value_spread: 60.0,
+                particle_color_alpha_mid: 10.0,
+                particle_color_alpha_spread: 16.0,
+                ..DisplayParams::default()
+            },
+            frame_count: 300,
+            camera: Camera::default(),
+        },
+        Reference {
+            name: "live-limits",
+            sim_params: SimParams {
+                particle_count: 500,
+                speed_limit: -1,
+                events: vec![
+                    SimEvent {
+                        frame: 100,
+                        change: SimChange::AccLimit(-4),
+                    },
+                    SimEvent {
+                        frame: 200,
+                        change: SimChange::SpeedLimit(1),
+                    },
+                ],
+                ..SimParams::default()
+            },
+            display_params: DisplayParams::default(),
+            frame_count: 300,
+            camera: Camera::default(),
+        },
+        Reference {
+            name: "brush-gaussian",
+            sim_params: SimParams {
+                seed: Seed::from_hash(0x0123456789abcdef),
+                particle_count: 500,
+                acc_limit: 4,
+                ..SimParams::default()
+            },
+            display_params: DisplayParams {
+                brush_radius: 0.5,
+                brush_falloff: BrushFalloff::Gaussian,
+                ..DisplayParams::default()
+            },
+            frame_count: 200,
+            camera: Camera::default(),
+        },
+        Reference {
+            name: "dither-direct-fade",
+            sim_params: SimParams::default(),
+            display_params: DisplayParams {
+                trail_fade: 2.0,
+                dither: Dither::Bayer,
+                ..DisplayParams::default()
+            },
+            frame_count: 300,
+            camera: Camera::default(),
+        },
+        Reference {
+            name: "camera-fit",
+            sim_params: SimParams::default(),
+            display_params: DisplayParams {
+                trail_style: TrailStyle::Segments,
+                ..DisplayParams::default()
+            },
+            frame_count: 300,
+            camera: Camera {
+                rotation: 30.0,
+                mode: CameraMode::Fit,
+                ..Camera::default()
+            },
+        },
+    ]
+}
+
+fn hash_reference(reference: &Reference) -> String {
+    let Reference {
+        name: _,
+        sim_params,
+        display_params,
+        frame_count,
+        camera,
+    } = reference;
+
+    let mut world = World::new(sim_params, display_params).unwrap();
+    let mut image =
+        Image::new(IMAGE_WIDTH, IMAGE_HEIGHT, display_params.background);
+    display_params.configure_image(&mut image);
+    let mut camera = camera.clone();
+    let trail_fade_amount = display_params.trail_fade_amount();
+    world.render_step(&mut image, &mut camera, trail_fade_amount);
+    for _ in 0..*frame_count {
+        world.update();
+        world.render_step(&mut image, &mut camera, trail_fade_amount);
+    }
+    image.tone_map();
+
+    let positions = world
+        .positions()
+        .iter()
+        .flat_map(|pos| [pos.x.to_le_bytes(), pos.y.to_le_bytes()])
+        .flatten()
+        .collect::<Vec<_>>();
+    let positions = md5::compute(positions);
+    let pixels = md5::compute(image.pixels().as_bytes());
+    format!("{positions:x} {pixels:x}")
+}
+
+fn read_goldens() -> BTreeMap<String, String> {
+    let goldens = std::fs::read_to_string(GOLDEN_PATH).unwrap_or_default();
+    goldens
+        .lines()
+        .filter_map(|line| line.split_once(' '))
+        .map(|(name, hashes)| (name.to_owned(), hashes.to_owned()))
+        .collect()
+}
+
+#[test]
+fn golden() {
+    let hashes = references()
+        .iter()
+        .map(|reference| (reference.name.to_owned(), hash_reference(reference)))
+        .collect::<BTreeMap<_, _>>();
+
+    if std::env::var_os(BLESS_ENV_VAR).is_some() {
+        let goldens = hashes
+            .iter()
+            .map(|(name, hashes)| format!("{name} {hashes}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        std::fs::write(GOLDEN_PATH, goldens + "\n").unwrap();
+        return;
+    }
+
+    let goldens = read_goldens();
+    let mismatches = hashes
+        .iter()
+        .filter(|(name, hashes)| goldens.get(*name) != Some(hashes))
+        .map(|(name, hashes)| {
+            let golden = goldens.get(name).map_or("(missing)", String::as_str);
+            format!("  {name}: expected {golden}, found {hashes}")
+        })
+        .collect::<Vec<_>>();
+    assert!(
+        mismatches.is_empty(),
+        "simulation output changed, run `just bless` if this is \
+         intentional\n{}",
+        mismatches.join("\n")
+    );
+}
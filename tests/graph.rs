@@ -0,0 +1,103 @@
+// Strongly connected components, the shortest cycle, and leaders of partner
+// graphs small enough to work out by hand.
+
+use followers::graph::PartnerGraph;
+
+#[test]
+fn disjoint_cycles() {
+    let partners = [[1, 1], [2, 2], [0, 0], [4, 4], [5, 5], [3, 3]];
+    let graph = PartnerGraph::new(&partners, &[]);
+    let (component_count, components) = graph.components();
+    assert_eq!(component_count, 2);
+    assert_eq!(components[0], components[1]);
+    assert_eq!(components[1], components[2]);
+    assert_eq!(components[3], components[4]);
+    assert_eq!(components[4], components[5]);
+    assert_ne!(components[0], components[3]);
+
+    let analysis = graph.analyze();
+    assert_eq!(analysis.component_count, 2);
+    assert_eq!(analysis.largest_component_size, 3);
+    assert_eq!(analysis.cyclic_component_count, 2);
+    assert_eq!(analysis.mutual_pair_count, 0);
+    assert_eq!(analysis.shortest_cycle, Some(3));
+    assert!(analysis.leaders.is_empty());
+}
+
+#[test]
+fn self_loop() {
+    // 0 follows itself and 1, 1 only itself
+    let partners = [[0, 1], [1, 1]];
+    let graph = PartnerGraph::new(&partners, &[]);
+    let (component_count, components) = graph.components();
+    assert_eq!(component_count, 2);
+    assert_ne!(components[0], components[1]);
+    assert_eq!(graph.analyze().shortest_cycle, Some(1));
+}
+
+#[test]
+fn shortest_cycle() {
+    // each node follows the next two around a ring of 6, so skipping ahead
+    // by two gets back in 3 steps
+    let partners = (0..6)
+        .map(|i| [(i + 1) % 6, (i + 2) % 6])
+        .collect::<Vec<_>>();
+    let analysis = PartnerGraph::new(&partners, &[]).analyze();
+    assert_eq!(analysis.component_count, 1);
+    assert_eq!(analysis.mutual_pair_count, 0);
+    assert_eq!(analysis.shortest_cycle, Some(3));
+    // every node is followed by two others
+    assert_eq!(analysis.in_degree_counts, [0, 0, 6]);
+}
+
+#[test]
+fn shortest_cycle_depth_limit() {
+    let ring = |len: usize| {
+        (0..len)
+            .map(|i| [(i + 1) % len, (i + 1) % len])
+            .collect::<Vec<_>>()
+    };
+    let partners = ring(64);
+    assert_eq!(
+        PartnerGraph::new(&partners, &[]).analyze().shortest_cycle,
+        Some(64)
+    );
+    let partners = ring(65);
+    assert_eq!(
+        PartnerGraph::new(&partners, &[]).analyze().shortest_cycle,
+        None
+    );
+}
+
+#[test]
+fn mutual_pairs() {
+    let partners = [[1, 2], [0, 2], [0, 1], [0, 1]];
+    let analysis = PartnerGraph::new(&partners, &[]).analyze();
+    assert_eq!(analysis.mutual_pair_count, 3);
+    assert_eq!(analysis.shortest_cycle, Some(2));
+    // nobody follows 3
+    assert_eq!(analysis.leaders, [3]);
+    assert_eq!(analysis.component_count, 2);
+    assert_eq!(analysis.largest_component_size, 3);
+}
+
+#[test]
+fn long_chain() {
+    // a million nodes deep, which would overflow the stack if the search
+    // recursed, closed into one big cycle at the end
+    let node_count = 1_000_000;
+    let partners = (0..node_count)
+        .map(|i| [(i + 1) % node_count, (i + 1) % node_count])
+        .collect::<Vec<_>>();
+    let (component_count, components) =
+        PartnerGraph::new(&partners, &[]).components();
+    assert_eq!(component_count, 1);
+    assert!(components.iter().all(|&component| component == 0));
+
+    // and left open, every node is its own component
+    let mut partners = partners;
+    partners[node_count - 1] = [node_count - 1, node_count - 1];
+    let (component_count, _components) =
+        PartnerGraph::new(&partners, &[]).components();
+    assert_eq!(component_count, node_count);
+}
@@ -0,0 +1,63 @@
+// Drawing into images directly, without accumulating.
+
+use followers::{background::Background, color::Color, image::Image};
+
+// a different color for every pixel of the original image
+fn pixel(x: usize, y: usize) -> Color {
+    Color::hex(((x as u32) << 24) | ((y as u32) << 16) | 0xff)
+}
+
+// resizing keeps the middle of the image, cropping it or padding it with the
+// background
+#[test]
+fn shrink_keeps_center() {
+    let (width, height) = (8, 6);
+    let mut image =
+        Image::new(width, height, Background::solid(Color::hex(0x000000ff)));
+    for y in 0..height {
+        for x in 0..width {
+            image.put_pixel(x, y, pixel(x, y));
+        }
+    }
+    image.resize(4, 2);
+    assert_eq!((image.width(), image.height()), (4, 2));
+    for y in 0..2 {
+        for x in 0..4 {
+            assert_eq!(image.pixels()[x + y * 4], pixel(x + 2, y + 2));
+        }
+    }
+}
+
+// a white pixel on black after the given number of fades
+fn faded(amount: f32, frame_count: usize) -> Color {
+    let mut image = Image::new(1, 1, Background::solid(Color::hex(0x000000ff)));
+    image.put_pixel(0, 0, Color::hex(0xffffffff));
+    for _ in 0..frame_count {
+        image.fade(amount);
+    }
+    image.pixels()[0]
+}
+
+#[test]
+fn slow_fade_keeps_trails() {
+    // 255 * 0.995^300 is about 56
+    let color = faded(0.005, 300);
+    assert!((50..=62).contains(&color.r), "{color:?}");
+    assert_eq!((color.r, color.g, color.b), (color.g, color.b, color.r));
+}
+
+#[test]
+fn fast_fade_erases_trails() {
+    assert_eq!(faded(0.05, 300), Color::hex(0x000000ff));
+}
+
+#[test]
+fn fade_is_smooth() {
+    // each frame only moves by a fraction of a step, but it still moves
+    let mut previous = 255;
+    for frame_count in (100..=1000).step_by(100) {
+        let r = faded(0.001, frame_count).r;
+        assert!(r < previous, "{frame_count}: {r}");
+        previous = r;
+    }
+}
@@ -0,0 +1,81 @@
+// OKLab conversions checked against the reference values published with it,
+// see https://bottosson.github.io/posts/oklab/
+
+use followers::color::{Color, LinearColor, OkLab, OkLch};
+
+fn linear(r: f32, g: f32, b: f32) -> LinearColor {
+    LinearColor { r, g, b, a: 1.0 }
+}
+
+fn assert_close(actual: f32, expected: f32, tolerance: f32, what: &str) {
+    assert!(
+        (actual - expected).abs() <= tolerance,
+        "{what}: expected {expected}, got {actual}"
+    );
+}
+
+#[test]
+fn white() {
+    let OkLab { l, a, b } = OkLab::from_linear(linear(1.0, 1.0, 1.0));
+    assert_close(l, 1.0, 1e-4, "l");
+    assert_close(a, 0.0, 1e-4, "a");
+    assert_close(b, 0.0, 1e-4, "b");
+}
+
+#[test]
+fn srgb_primaries() {
+    let cases = [
+        (linear(1.0, 0.0, 0.0), (0.627955, 0.224863, 0.125846)),
+        (linear(0.0, 1.0, 0.0), (0.866440, -0.233888, 0.179498)),
+        (linear(0.0, 0.0, 1.0), (0.452014, -0.032457, -0.311528)),
+    ];
+    for (color, (l, a, b)) in cases {
+        let lab = OkLab::from_linear(color);
+        assert_close(lab.l, l, 1e-4, &format!("{color:?} l"));
+        assert_close(lab.a, a, 1e-4, &format!("{color:?} a"));
+        assert_close(lab.b, b, 1e-4, &format!("{color:?} b"));
+    }
+}
+
+#[test]
+fn round_trip() {
+    let steps = [0.0, 0.01, 0.2, 0.5, 0.8, 1.0];
+    for r in steps {
+        for g in steps {
+            for b in steps {
+                let color = linear(r, g, b);
+                let back =
+                    OkLab::from_linear(color).to_lch().to_lab().to_linear();
+                for (x, y) in [(back.r, r), (back.g, g), (back.b, b)] {
+                    assert_close(x, y, 1e-4, &format!("{color:?}"));
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn gamut_map() {
+    for l in [0.2, 0.5, 0.7, 0.9] {
+        for h in (0..360).step_by(15) {
+            let h = h as f32;
+            let lch = OkLch { l, c: 0.4, h };
+            let mapped = lch.gamut_map();
+            assert_eq!(mapped.l, l);
+            assert_eq!(mapped.h, h);
+            assert!(mapped.c < lch.c, "{lch:?}");
+            let LinearColor { r, g, b, a: _ } = mapped.to_lab().to_linear();
+            for x in [r, g, b] {
+                assert!((-1e-4..=1.0 + 1e-4).contains(&x), "{lch:?}: {x}");
+            }
+        }
+    }
+}
+
+#[test]
+fn in_gamut_unchanged() {
+    let lch = OkLab::from_linear(linear(0.8, 0.3, 0.1)).to_lch();
+    assert_eq!(lch.gamut_map(), lch);
+    // the most saturated red is right at the edge
+    assert_eq!(Color::oklcha(62.7955, 0.2577, 29.23, 100.0).r, 255);
+}
@@ -0,0 +1,100 @@
+// Palette extraction from PNG images and sampling of weighted swatches.
+
+use followers::{
+    background::Background,
+    color::Color,
+    image::Image,
+    palette::{extract_swatches, Palette, PaletteMode},
+};
+
+fn png(
+    width: usize,
+    height: usize,
+    color: impl Fn(usize, usize) -> Color,
+) -> Vec<u8> {
+    let mut image =
+        Image::new(width, height, Background::solid(Color::transparent()));
+    for y in 0..height {
+        for x in 0..width {
+            image.put_pixel(x, y, color(x, y));
+        }
+    }
+    image.encode_png().unwrap()
+}
+
+#[test]
+fn decode_png() {
+    let colors = [
+        Color::hex(0xff000080),
+        Color::hex(0x00ff00ff),
+        Color::hex(0x0000ffff),
+        Color::hex(0x12345678),
+    ];
+    let png = png(2, 2, |x, y| colors[x + y * 2]);
+    let (width, height, pixels) = Image::decode_png(&png).unwrap();
+    assert_eq!((width, height), (2, 2));
+    assert_eq!(pixels, colors);
+}
+
+#[test]
+fn extract_weights_by_coverage() {
+    let red = Color::hex(0xff0000ff);
+    let blue = Color::hex(0x0000ffff);
+    let png = png(40, 40, |x, _y| if x < 30 { red } else { blue });
+    // only two distinct colors, so asking for more finds two
+    let palette = Palette::from_png(&png, 4).unwrap();
+    assert_eq!(palette.mode, PaletteMode::Swatches);
+    assert_eq!(palette.swatches, [red, blue]);
+    assert_eq!(palette.swatch_weights, [1.5, 0.5]);
+}
+
+#[test]
+fn extract_ignores_transparent_pixels() {
+    let green = Color::hex(0x00ff00ff);
+    let pixels = (0..100)
+        .map(|idx| {
+            if idx < 10 {
+                green
+            } else {
+                Color::hex(0xff00ff10)
+            }
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(extract_swatches(&pixels, 3), [(green, 1.0)]);
+    assert!(extract_swatches(&[Color::transparent(); 4], 3).is_empty());
+}
+
+#[test]
+fn extract_is_deterministic() {
+    let png = png(64, 64, |x, y| {
+        Color::hsva(x as f32 * 5.0, 80.0, 40.0 + y as f32, 100.0)
+    });
+    let a = Palette::from_png(&png, 5).unwrap();
+    let b = Palette::from_png(&png, 5).unwrap();
+    assert_eq!(a, b);
+    assert_eq!(a.swatches.len(), 5);
+    let total = a.swatch_weights.iter().sum::<f32>();
+    assert!((total - 5.0).abs() < 1e-3, "{total}");
+    // most common first
+    assert!(a.swatch_weights.windows(2).all(|pair| pair[0] >= pair[1]));
+}
+
+#[test]
+fn weighted_swatches() {
+    let colors = [
+        Color::hex(0xff0000ff),
+        Color::hex(0x00ff00ff),
+        Color::hex(0x0000ffff),
+    ];
+    let palette = Palette {
+        mode: PaletteMode::Swatches,
+        swatches: colors.to_vec(),
+        swatch_weights: vec![2.0, 0.0],
+        ..Palette::default()
+    };
+    // the missing weight is 1, so red gets two thirds and green none
+    assert_eq!(palette.sample(0.0), Some(colors[0]));
+    assert_eq!(palette.sample(0.6), Some(colors[0]));
+    assert_eq!(palette.sample(0.7), Some(colors[2]));
+    assert_eq!(palette.sample(1.0), Some(colors[2]));
+}
@@ -0,0 +1,93 @@
+// Importing partner graphs from CSV and DOT files.
+
+use followers::graph::{parse_partners, validate_partners, PartnerGraph};
+
+fn error(s: &str) -> String {
+    format!("{:#}", parse_partners(s).unwrap_err())
+}
+
+#[test]
+fn csv() {
+    let csv = "\
+        # particle, first partner, second partner\ni,j,k\n\n2, 0,1\n0 ,1, 2   \
+               # comment after a row\n1,2,0\n3,\t0, 2\n";
+    assert_eq!(
+        parse_partners(csv).unwrap(),
+        [[1, 2], [2, 0], [0, 1], [0, 2]]
+    );
+}
+
+#[test]
+fn dot() {
+    let dot = r#"
+        digraph partners {
+            // one edge per statement, labeled with the partner slot
+            n0 -> n1 [label="1"];
+            n0 -> n2 [label="2"];
+            /* unlabeled edges fill the slots in order */
+            1 -> 2; 1 -> 0;
+            "2" -> 3 -> 0;
+            2 -> 1;
+            3 -> 1 [label = "2"]
+        }
+    "#;
+    assert_eq!(
+        parse_partners(dot).unwrap(),
+        [[1, 2], [2, 0], [3, 1], [0, 1]]
+    );
+}
+
+#[test]
+fn dot_round_trip() {
+    let partners = [[1, 2], [2, 3], [3, 0], [0, 1]];
+    let dot = PartnerGraph::new(&partners, &[]).generate_dot();
+    assert_eq!(parse_partners(&dot).unwrap(), partners);
+}
+
+#[test]
+fn out_of_range() {
+    assert!(error("0,1,2\n1,2,0\n2,0,3\n").contains("must be less than 3"));
+    assert!(validate_partners(&[[1, 2], [2, 0], [0, 5]]).is_err());
+}
+
+// indices are checked before room is made for them
+#[test]
+fn past_the_limit() {
+    assert!(error("1000000000000,1,2\n").contains("past the limit"));
+    assert!(error(&format!("0,1,2\n{},0,1\n", usize::MAX))
+        .contains("past the limit"));
+    assert!(error("digraph { 0 -> 1; 0 -> 2; 1000000000000 -> 0 }")
+        .contains("past the limit"));
+}
+
+// only the first row can be a header, and only the header makes it DOT
+#[test]
+fn csv_errors() {
+    assert!(error("i,j,k\nparticle,partner,partner\n0,1,2\n")
+        .contains("line 2: expected `i,j,k`"));
+    assert!(error("0,1,2\n1,2,0\n2,0,x\n").contains("line 3"));
+    assert_eq!(
+        parse_partners("# 0 -> 1 -> 2\n0,1,2\n1,2,0\n2,0,1\n").unwrap(),
+        [[1, 2], [2, 0], [0, 1]]
+    );
+}
+
+#[test]
+fn own_partner() {
+    assert!(error("0,1,2\n1,1,0\n2,0,1\n").contains("its own partner"));
+    assert!(error("0,1,1\n1,2,0\n2,0,1\n").contains("must be distinct"));
+}
+
+#[test]
+fn wrong_number_of_partners() {
+    assert!(error(
+        "digraph { 0 -> 1; 0 -> 2; 0 -> 2; 1 -> 2; 1 -> 0; 2 -> 0; 2 -> 1 }"
+    )
+    .contains("more than two partners"));
+    assert!(error("digraph { 0 -> 1; 0 -> 2; 1 -> 2; 2 -> 0; 2 -> 1 }")
+        .contains("particle 1 does not have two partners"));
+    assert!(error("0,1,2\n2,0,1\n").contains("particle 1 does not have"));
+    assert!(error("0,1,2,3\n1,2,0\n2,0,1\n").contains("expected `i,j,k`"));
+    // at least 3 particles
+    assert!(error("0,1,2\n1,0,2\n").contains("at least 3"));
+}
@@ -0,0 +1,77 @@
+// Recoloring a running world, which has to match a world that had the new
+// display params from the start.
+
+use followers::{
+    background::Background,
+    palette::{NamedPalette, Palette, PaletteMode},
+    world::{ColorDistribution, ColorMode, DisplayParams, SimParams, World},
+};
+
+const PARTICLE_COUNT: usize = 30;
+const FRAME_COUNT: usize = 40;
+
+fn run(display_params: &DisplayParams) -> World {
+    let sim_params = SimParams {
+        particle_count: PARTICLE_COUNT,
+        ..SimParams::default()
+    };
+    let mut world = World::new(&sim_params, display_params).unwrap();
+    for _ in 0..FRAME_COUNT {
+        world.update();
+    }
+    world
+}
+
+#[test]
+fn recolor_matches_new_world() {
+    let recolored_params = [
+        DisplayParams {
+            particle_color_hue_mid: 300.0,
+            particle_color_hue_spread: 60.0,
+            particle_color_value_spread: 40.0,
+            particle_color_alpha_spread: 10.0,
+            ..DisplayParams::default()
+        },
+        DisplayParams {
+            color_distribution: ColorDistribution::Component,
+            ..DisplayParams::default()
+        },
+        DisplayParams {
+            color_mode: ColorMode::Speed,
+            palette: Palette {
+                mode: PaletteMode::Named,
+                named: NamedPalette::Magma,
+                ..Palette::default()
+            },
+            ..DisplayParams::default()
+        },
+        DisplayParams {
+            color_cycle_speed: 5.0,
+            color_cycle_phase: 90.0,
+            ..DisplayParams::default()
+        },
+    ];
+    for display_params in recolored_params {
+        let mut world = run(&DisplayParams::default());
+        world.recolor(&display_params);
+        let background = Background::default();
+        assert_eq!(
+            world.generate_svg(&background),
+            run(&display_params).generate_svg(&background)
+        );
+    }
+}
+
+#[test]
+fn recolor_keeps_running() {
+    let mut world = run(&DisplayParams::default());
+    let before = world.generate_svg(&Background::default());
+    world.recolor(&DisplayParams {
+        particle_color_saturation_mid: 20.0,
+        ..DisplayParams::default()
+    });
+    world.recolor(&DisplayParams::default());
+    assert_eq!(world.generate_svg(&Background::default()), before);
+    world.update();
+    assert_ne!(world.generate_svg(&Background::default()), before);
+}
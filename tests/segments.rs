@@ -0,0 +1,53 @@
+// Segments joined end to end only cover their shared endpoint once, so a
+// translucent trail doesn't get darker dots at every frame.
+
+use followers::{background::Background, color::Color, image::Image};
+
+const COLOR: Color = Color::hex(0xffffff80);
+
+fn image() -> Image {
+    Image::new(8, 1, Background::solid(Color::hex(0x000000ff)))
+}
+
+#[test]
+fn joint_is_covered_once() {
+    let mut image = image();
+    image.draw_segment(0.5, 0.5, 2.5, 0.5, COLOR);
+    image.draw_segment(2.5, 0.5, 4.5, 0.5, COLOR);
+
+    let mut single =
+        Image::new(1, 1, Background::solid(Color::hex(0x000000ff)));
+    single.blend_pixel(0, 0, COLOR);
+    let single = single.pixels()[0];
+
+    // the start of the first segment is left out too
+    let background = Color::hex(0x000000ff);
+    assert_eq!(
+        image.pixels(),
+        [
+            background, single, single, single, single, background, background,
+            background
+        ]
+    );
+}
+
+#[test]
+fn joined_like_one_segment() {
+    let mut joined = image();
+    joined.draw_segment(0.5, 0.5, 2.5, 0.5, COLOR);
+    joined.draw_segment(2.5, 0.5, 4.5, 0.5, COLOR);
+    let mut whole = image();
+    whole.draw_segment(0.5, 0.5, 4.5, 0.5, COLOR);
+    assert_eq!(joined.pixels(), whole.pixels());
+}
+
+// a particle that stays put still shows up
+#[test]
+fn zero_length_is_a_dot() {
+    let mut segment = image();
+    segment.draw_segment(2.25, 0.5, 2.25, 0.5, COLOR);
+    let mut dot = image();
+    dot.draw_particle(2.25, 0.5, COLOR);
+    assert_eq!(segment.pixels(), dot.pixels());
+    assert_ne!(segment.pixels(), image().pixels());
+}
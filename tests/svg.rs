@@ -0,0 +1,97 @@
+// The paths in saved SVG files, which split where particle colors change.
+
+use followers::{
+    background::Background,
+    world::{ColorMode, DisplayParams, SimParams, World},
+};
+
+const PARTICLE_COUNT: usize = 20;
+const FRAME_COUNT: usize = 50;
+
+fn svg(display_params: &DisplayParams) -> String {
+    let sim_params = SimParams {
+        particle_count: PARTICLE_COUNT,
+        ..SimParams::default()
+    };
+    let mut world = World::new(&sim_params, display_params).unwrap();
+    for _ in 0..FRAME_COUNT {
+        world.update();
+    }
+    world.generate_svg(&Background::default())
+}
+
+fn paths(svg: &str) -> Vec<(&str, usize)> {
+    svg.lines()
+        .filter(|line| line.trim_start().starts_with("<path"))
+        .map(|line| {
+            let stroke = line.split("stroke=\"#").nth(1).unwrap();
+            (&stroke[..8], line.matches(" L ").count() + 1)
+        })
+        .collect()
+}
+
+#[test]
+fn fixed_colors() {
+    let svg = svg(&DisplayParams::default());
+    let paths = paths(&svg);
+    assert_eq!(paths.len(), PARTICLE_COUNT);
+    assert!(paths
+        .iter()
+        .all(|(_color, points)| *points == FRAME_COUNT + 1));
+}
+
+#[test]
+fn cycling_colors() {
+    let svg = svg(&DisplayParams {
+        color_cycle_speed: 5.0,
+        ..DisplayParams::default()
+    });
+    let paths = paths(&svg);
+    assert!(paths.len() > PARTICLE_COUNT);
+    // neighbouring paths share their end points, and change color there
+    let points = paths
+        .iter()
+        .map(|(_color, points)| points - 1)
+        .sum::<usize>();
+    assert_eq!(points, PARTICLE_COUNT * FRAME_COUNT);
+    assert!(paths.windows(2).any(|pair| pair[0].0 != pair[1].0));
+}
+
+#[test]
+fn changing_colors() {
+    let svg = svg(&DisplayParams {
+        color_mode: ColorMode::Age,
+        ..DisplayParams::default()
+    });
+    let paths = paths(&svg);
+    let points = paths
+        .iter()
+        .map(|(_color, points)| points - 1)
+        .sum::<usize>();
+    assert_eq!(points, PARTICLE_COUNT * FRAME_COUNT);
+}
+
+// spreads reaching past the ends of the sliders are moved to fit, so no
+// particle ends up fully dark or transparent
+#[test]
+fn extreme_spreads() {
+    for (value_mid, alpha_mid) in [(1.0, 1.0), (100.0, 100.0), (1.0, 100.0)] {
+        let svg = svg(&DisplayParams {
+            particle_color_value_mid: value_mid,
+            particle_color_value_spread: 100.0,
+            particle_color_alpha_mid: alpha_mid,
+            particle_color_alpha_spread: 100.0,
+            ..DisplayParams::default()
+        });
+        let paths = paths(&svg);
+        assert_eq!(paths.len(), PARTICLE_COUNT);
+        for (color, _points) in paths {
+            assert_ne!(&color[6..], "00", "{value_mid} {alpha_mid}: {color}");
+            assert_ne!(
+                &color[..6],
+                "000000",
+                "{value_mid} {alpha_mid}: {color}"
+            );
+        }
+    }
+}
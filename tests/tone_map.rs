@@ -0,0 +1,83 @@
+// Tone mapping curves, and log tone mapping keeping the brightest pixel white
+// as the image is drawn and faded.
+
+use followers::{
+    accumulator::{ToneMap, ToneMapping},
+    background::Background,
+    color::Color,
+    image::{Image, Render},
+};
+
+#[test]
+fn black_stays_black() {
+    for &tone_mapping in ToneMapping::ALL {
+        assert_eq!(tone_mapping.map(0.0, 4.0), 0.0, "{}", tone_mapping.name());
+    }
+}
+
+#[test]
+fn monotonic() {
+    for &tone_mapping in ToneMapping::ALL {
+        let mut previous = 0.0;
+        for idx in 1..=1000 {
+            let x = idx as f32 / 100.0;
+            let y = tone_mapping.map(x, 10.0);
+            assert!(y > previous, "{} at {x}", tone_mapping.name());
+            previous = y;
+        }
+    }
+}
+
+#[test]
+fn white() {
+    assert_eq!(ToneMapping::Linear.map(1.0, 1.0), 1.0);
+    assert_eq!(ToneMapping::Reinhard.map(1.0, 1.0), 0.5);
+    // Narkowicz's ACES fit puts white at about 80% and levels off just past 1
+    let filmic = ToneMapping::Filmic.map(1.0, 1.0);
+    assert!((filmic - 0.8038).abs() < 1e-3, "{filmic}");
+    let filmic = ToneMapping::Filmic.map(1000.0, 1.0);
+    assert!((1.0..1.04).contains(&filmic), "{filmic}");
+    // reinhard only ever approaches 1
+    assert!(ToneMapping::Reinhard.map(1000.0, 1.0) < 1.0);
+    // log is scaled so the white point maps to 1
+    for white in [0.5, 1.0, 8.0, 100.0] {
+        let log = ToneMapping::Log.map(white, white);
+        assert!((log - 1.0).abs() < 1e-6, "{white}: {log}");
+    }
+    assert_eq!(ToneMapping::Log.map(1.0, 0.0), 0.0);
+}
+
+fn brightest(image: &Image) -> u8 {
+    image.pixels().iter().map(|p| p.r).max().unwrap()
+}
+
+#[test]
+fn log_white_follows_brightest() {
+    let mut image =
+        Image::new(16, 16, Background::solid(Color::hex(0x000000ff)));
+    image.set_render(Render::Accumulate(ToneMap {
+        tone_mapping: ToneMapping::Log,
+        exposure: 0.0,
+        gamma: 1.0,
+    }));
+    for _ in 0..10 {
+        image.draw_particle(8.5, 8.5, Color::hex(0x80808040));
+    }
+    image.tone_map();
+    assert_eq!(brightest(&image), 255);
+
+    // brighter overlaps raise the white point
+    for _ in 0..100 {
+        image.draw_particle(4.5, 4.5, Color::hex(0xffffffff));
+    }
+    image.tone_map();
+    assert_eq!(brightest(&image), 255);
+    assert!(image.pixels()[8 + 8 * 16].r < 255);
+
+    // and fading lowers it again
+    for _ in 0..10 {
+        image.fade(0.5);
+    }
+    image.tone_map();
+    assert_eq!(brightest(&image), 255);
+}